@@ -0,0 +1,253 @@
+// Ensembles symboliques d'entiers non signés de taille fixe.
+//
+// Un `BddSet` représente un ensemble d'entiers de `n` bits par sa fonction
+// caractéristique : un BDD sur `n` variables, vrai pour les affectations qui
+// encodent un élément de l'ensemble. L'encodage est celui de `BitVec` : la
+// i-ème variable de la liste donnée à la construction est le bit de poids i.
+// Les opérations ensemblistes sont les opérations booléennes sur les fonctions
+// caractéristiques, et le cardinal est le nombre de modèles de celles-ci.
+//
+// Les éléments sont des `u64` : un ensemble compte donc au plus 64 variables,
+// et les entiers qui ne s'écrivent pas sur ce nombre de bits n'en font jamais
+// partie. Les insérer est une erreur, plutôt que d'insérer silencieusement
+// leurs bits de poids faible.
+
+use std::hash::Hash;
+use super::{Bdd, BitVec, Context};
+
+#[derive(Clone)]
+pub struct BddSet<'arena, V> {
+    vars: Vec<V>,
+    bits: BitVec<'arena, V>,
+    f: Bdd<'arena, V>,
+}
+
+impl<'arena, V: Hash + Copy + Ord> BddSet<'arena, V> {
+    // La fonction `empty` renvoie l'ensemble vide d'entiers encodés sur les
+    // variables `vars`, du poids faible au poids fort.
+    pub fn empty(ctx: Context<'arena, V>, vars: &[V]) -> Self {
+        return BddSet::from_bdd(ctx, vars, ctx.false_())
+    }
+
+    // La fonction `full` renvoie l'ensemble de tous les entiers encodables sur
+    // les variables `vars`.
+    pub fn full(ctx: Context<'arena, V>, vars: &[V]) -> Self {
+        return BddSet::from_bdd(ctx, vars, ctx.true_())
+    }
+
+    // La fonction `from_bdd` renvoie l'ensemble de fonction caractéristique
+    // `f`, qui ne doit dépendre que des variables de `vars`.
+    pub fn from_bdd(ctx: Context<'arena, V>, vars: &[V], f: Bdd<'arena, V>) -> Self {
+        assert!(vars.len() <= 64, "set elements wider than 64 bits");
+        return BddSet { vars: vars.to_vec(), bits: BitVec::new(ctx, vars), f: f }
+    }
+
+    // La méthode `bdd` renvoie la fonction caractéristique de l'ensemble.
+    pub fn bdd(&self) -> Bdd<'arena, V> {
+        return self.f
+    }
+
+    pub fn vars(&self) -> &[V] {
+        return &self.vars
+    }
+
+    // La méthode privée `constant` renvoie le vecteur de bits constant `x`,
+    // de la largeur des éléments de l'ensemble.
+    fn constant(&self, x: u64) -> BitVec<'arena, V> {
+        return BitVec::constant(self.f.ctx, self.vars.len(), x)
+    }
+
+    // La méthode privée `fits` indique si `x` s'écrit sur la largeur des
+    // éléments de l'ensemble.
+    fn fits(&self, x: u64) -> bool {
+        return self.vars.len() == 64 || x >> self.vars.len() == 0
+    }
+
+    // La méthode privée `singleton` renvoie la fonction caractéristique de
+    // l'ensemble `{x}`.
+    fn singleton(&self, x: u64) -> Bdd<'arena, V> {
+        return self.bits.equal(&self.constant(x))
+    }
+
+    // La méthode `insert` ajoute `x` à l'ensemble. Elle panique si `x` ne
+    // s'écrit pas sur la largeur des éléments.
+    pub fn insert(&mut self, x: u64) {
+        assert!(self.fits(x), "element too wide for the set");
+        self.f |= self.singleton(x)
+    }
+
+    // La méthode `remove` retire `x` de l'ensemble ; elle est sans effet si
+    // `x` est trop large pour en faire partie.
+    pub fn remove(&mut self, x: u64) {
+        if self.fits(x) {
+            self.f &= !self.singleton(x)
+        }
+    }
+
+    // La méthode `insert_range` ajoute à l'ensemble les entiers de `lo` à
+    // `hi`, inclus. Elle panique, comme `insert`, si `lo` ou `hi` ne s'écrit
+    // pas sur la largeur des éléments.
+    pub fn insert_range(&mut self, lo: u64, hi: u64) {
+        assert!(self.fits(lo) && self.fits(hi), "element too wide for the set");
+        self.f |= self.constant(lo).le(&self.bits) & self.bits.le(&self.constant(hi))
+    }
+
+    pub fn contains(&self, x: u64) -> bool {
+        if !self.fits(x) {
+            return false
+        }
+        return self.f.eval(|v| {
+            let i = self.vars.iter().position(|&w| w == v).unwrap();
+            x >> i & 1 == 1
+        })
+    }
+
+    // Les méthodes `union`, `intersection` et `difference` supposent que les
+    // deux ensembles sont encodés sur les mêmes variables.
+    pub fn union(&self, other: &Self) -> Self {
+        assert!(self.vars == other.vars, "sets over different variables");
+        return BddSet { f: self.f | other.f, ..self.clone() }
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        assert!(self.vars == other.vars, "sets over different variables");
+        return BddSet { f: self.f & other.f, ..self.clone() }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        assert!(self.vars == other.vars, "sets over different variables");
+        return BddSet { f: self.f & !other.f, ..self.clone() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.f == self.f.ctx.false_()
+    }
+
+    // La méthode `len` renvoie le cardinal de l'ensemble, qui doit tenir dans
+    // un `u64`.
+    pub fn len(&self) -> u64 {
+        let mut vars = self.vars.clone();
+        vars.sort();
+        return self.f.nsat(&vars)
+    }
+
+    // La méthode privée `min` renvoie le plus petit élément de l'ensemble de
+    // fonction caractéristique `f`, en fixant les bits du poids fort au poids
+    // faible, à 0 lorsque c'est possible.
+    fn min(&self, mut f: Bdd<'arena, V>) -> Option<u64> {
+        if f == f.ctx.false_() {
+            return None
+        }
+        let mut x = 0;
+        for (i, &b) in self.bits.bits().iter().enumerate().rev() {
+            let f0 = f & !b;
+            if f0 != f.ctx.false_() {
+                f = f0
+            } else {
+                f &= b;
+                x |= 1 << i
+            }
+        }
+        return Some(x)
+    }
+
+    // La méthode `iter` renvoie un itérateur sur les éléments de l'ensemble,
+    // dans l'ordre croissant.
+    pub fn iter(&self) -> Iter<'_, 'arena, V> {
+        return Iter { set: self, rest: self.f }
+    }
+}
+
+pub struct Iter<'a, 'arena, V> {
+    set: &'a BddSet<'arena, V>,
+    rest: Bdd<'arena, V>,
+}
+
+impl<'a, 'arena, V: Hash + Copy + Ord> Iterator for Iter<'a, 'arena, V> {
+    type Item = u64;
+    fn next(&mut self) -> Option<u64> {
+        let x = self.set.min(self.rest)?;
+        self.rest &= !self.set.singleton(x);
+        return Some(x)
+    }
+}
+
+#[test]
+fn test_bddset() {
+    use std::collections::BTreeSet;
+    super::use_bdd(|ctx| {
+        let vars: Vec<u8> = (0..6).rev().collect();
+        let mut s = BddSet::empty(ctx, &vars);
+        let mut expected = BTreeSet::new();
+        assert!(s.is_empty());
+        for x in [3, 17, 42, 63, 0, 17] {
+            s.insert(x);
+            expected.insert(x);
+        }
+        s.insert_range(20, 30);
+        expected.extend(20..=30);
+        s.remove(25);
+        expected.remove(&25);
+        assert_eq!(s.len(), expected.len() as u64);
+        assert_eq!(s.iter().collect::<Vec<u64>>(), expected.iter().copied().collect::<Vec<u64>>());
+        assert!(s.contains(42) && !s.contains(25) && !s.contains(31));
+
+        let mut t = BddSet::empty(ctx, &vars);
+        t.insert_range(10, 40);
+        let u: BTreeSet<u64> = (10..=40).collect();
+        let check = |s: BddSet<u8>, e: Vec<u64>| assert_eq!(s.iter().collect::<Vec<u64>>(), e);
+        check(s.union(&t), expected.union(&u).copied().collect());
+        check(s.intersection(&t), expected.intersection(&u).copied().collect());
+        check(s.difference(&t), expected.difference(&u).copied().collect());
+        assert_eq!(BddSet::full(ctx, &vars).len(), 64);
+    });
+}
+
+#[test]
+fn test_bddset_width() {
+    super::use_bdd(|ctx| {
+        let vars: Vec<u8> = (0..3).collect();
+        let mut s = BddSet::empty(ctx, &vars);
+        s.insert_range(6, 7);
+        s.remove(14);
+        assert!(s.contains(6) && !s.contains(14) && !s.contains(u64::MAX));
+        assert_eq!(s.iter().collect::<Vec<u64>>(), vec![6, 7]);
+
+        // Les éléments de 64 bits occupent toute la largeur d'un `u64`.
+        let vars: Vec<u8> = (0..64).collect();
+        let mut s = BddSet::empty(ctx, &vars);
+        s.insert(u64::MAX);
+        s.insert_range(0, 1);
+        assert!(s.contains(u64::MAX) && s.contains(1) && !s.contains(2));
+        assert_eq!(s.iter().collect::<Vec<u64>>(), vec![0, 1, u64::MAX]);
+        s.remove(u64::MAX);
+        assert_eq!(s.len(), 2);
+    });
+}
+
+#[test]
+#[should_panic(expected = "element too wide for the set")]
+fn test_bddset_insert_too_wide() {
+    super::use_bdd(|ctx| {
+        let mut s = BddSet::empty(ctx, &[0u8, 1, 2]);
+        s.insert(8);
+    });
+}
+
+#[test]
+#[should_panic(expected = "element too wide for the set")]
+fn test_bddset_range_too_wide() {
+    super::use_bdd(|ctx| {
+        let mut s = BddSet::empty(ctx, &[0u8, 1, 2]);
+        s.insert_range(3, 8);
+    });
+}
+
+#[test]
+#[should_panic(expected = "set elements wider than 64 bits")]
+fn test_bddset_too_many_vars() {
+    super::use_bdd(|ctx| {
+        let vars: Vec<u8> = (0..65).collect();
+        BddSet::empty(ctx, &vars);
+    });
+}
@@ -1,3 +1,4 @@
+use bdd::bdd;
 use bdd::raw::{Bdd, Context};
 use std::time::Instant;
 
@@ -12,28 +13,19 @@ fn queens_s<'a>(ctx: &mut Context<'a, u16>, i: i8, j: i8, n: i8) -> Bdd<'a, u16>
         if i == row {
             for column in (0..n).rev() {
                 if j == column {
-                    let x = ctx.var(pos(row, column, n));
-                    out = ctx.and(out, x);
+                    out = bdd!(ctx, out & var(pos(row, column, n)));
                 } else {
-                    let x = ctx.var(pos(row, column, n));
-                    let nx = ctx.not(x);
-                    out = ctx.and(out, nx);
+                    out = bdd!(ctx, out & !var(pos(row, column, n)));
                 }
             }
         } else {
             let row_diff = (i - row).abs();
             if j + row_diff < n {
-                let x = ctx.var(pos(row, j + row_diff, n));
-                let nx = ctx.not(x);
-                out = ctx.and(out, nx);
+                out = bdd!(ctx, out & !var(pos(row, j + row_diff, n)));
             }
-            let x = ctx.var(pos(row, j, n));
-            let nx = ctx.not(x);
-            out = ctx.and(out, nx);
+            out = bdd!(ctx, out & !var(pos(row, j, n)));
             if row_diff <= j {
-                let x = ctx.var(pos(row, j - row_diff, n));
-                let nx = ctx.not(x);
-                out = ctx.and(out, nx);
+                out = bdd!(ctx, out & !var(pos(row, j - row_diff, n)));
             }
         }
     }
@@ -11,7 +11,7 @@ impl<'a> PigeonHolePb<'a> {
     fn var(&self, pigeon: u8, hole: u8) -> Bdd<'a, u16> {
         assert!(pigeon < self.pigeons);
         assert!(hole < self.holes);
-        self.bddctx.var(pigeon as u16  as u16 + hole as u16 * self.pigeons as u16)
+        self.bddctx.var(pigeon as u16 + hole as u16 * self.pigeons as u16)
     }
 
     fn pigeon_has_hole(&self, pigeon: u8) -> Bdd<'a, u16> {
@@ -1,3 +1,4 @@
+use bdd::bdd;
 use bdd::raw::{Bdd, Context};
 use std::time::Instant;
 
@@ -11,7 +12,7 @@ impl<'a> PigeonHolePb<'a> {
     fn var(&mut self, pigeon: u8, hole: u8) -> Bdd<'a, u16> {
         assert!(pigeon < self.pigeons);
         assert!(hole < self.holes);
-        self.bddctx.var(pigeon as u16  as u16 + hole as u16 * self.pigeons as u16)
+        self.bddctx.var(pigeon as u16 + hole as u16 * self.pigeons as u16)
     }
 
     fn pigeon_has_hole(&mut self, pigeon: u8) -> Bdd<'a, u16> {
@@ -39,9 +40,7 @@ impl<'a> PigeonHolePb<'a> {
                 for p2 in (p1+1)..self.pigeons {
                     let x1 = self.var(p1, h);
                     let x2 = self.var(p2, h);
-                    let x12 = self.bddctx.and(x1, x2);
-                    let nx12 = self.bddctx.not(x12);
-                    res = self.bddctx.and(res, nx12)
+                    res = bdd!(self.bddctx, res & !(x1 & x2))
                 }
             }
         }
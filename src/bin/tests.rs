@@ -1,3 +1,6 @@
+// Ces tests vérifient volontairement des identités comme `x & x == x`.
+#![allow(clippy::eq_op)]
+
 use bdd::use_bdd;
 
 fn main() {
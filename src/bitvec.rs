@@ -0,0 +1,220 @@
+// Vecteurs de bits symboliques, pour encoder des entiers non signés de taille
+// fixe et leur arithmétique sur l'interface de haut niveau.
+//
+// Un `BitVec` est un vecteur de BDDs, le bit de poids faible en premier.
+// L'arithmétique est modulaire (modulo 2^n pour une largeur n), et les
+// opérations binaires supposent que leurs opérandes ont la même largeur.
+//
+// L'ordre des variables a une grande influence sur la taille des BDDs
+// obtenus : pour comparer ou additionner deux vecteurs, il faut entrelacer
+// leurs bits, faute de quoi les BDDs sont de taille exponentielle. Le
+// constructeur `interleaved` crée des vecteurs ainsi entrelacés.
+
+use std::hash::Hash;
+use std::ops::{Add, Mul, Shl, Shr, Sub};
+use super::{Bdd, Context};
+
+#[derive(Clone)]
+pub struct BitVec<'arena, V> {
+    ctx: Context<'arena, V>,
+    bits: Vec<Bdd<'arena, V>>,
+}
+
+impl<'arena, V: Hash + Copy + Ord> BitVec<'arena, V> {
+    // La fonction `constant` renvoie le vecteur de largeur `width` valant
+    // `value` (tronquée à `width` bits).
+    pub fn constant(ctx: Context<'arena, V>, width: usize, value: u64) -> Self {
+        let bits = (0..width).map(|i| {
+            if i < 64 && value >> i & 1 == 1 { ctx.true_() } else { ctx.false_() }
+        }).collect();
+        return BitVec { ctx: ctx, bits: bits }
+    }
+
+    // La fonction `new` renvoie le vecteur dont les bits sont les variables
+    // de `vars`, du poids faible au poids fort.
+    pub fn new(ctx: Context<'arena, V>, vars: &[V]) -> Self {
+        return BitVec { ctx: ctx, bits: vars.iter().map(|&v| ctx.var(v)).collect() }
+    }
+
+    // La fonction `from_bits` renvoie le vecteur formé des BDDs de `bits`, du
+    // poids faible au poids fort.
+    pub fn from_bits(ctx: Context<'arena, V>, bits: Vec<Bdd<'arena, V>>) -> Self {
+        return BitVec { ctx: ctx, bits: bits }
+    }
+
+    // La fonction `interleaved` renvoie `count` vecteurs de largeur `width`
+    // dont les bits sont entrelacés : le bit `j` du vecteur `i` est la variable
+    // `var(j * count + i)`. Les bits de poids faible sont donc en haut des
+    // BDDs, ce qui convient à l'addition comme aux comparaisons.
+    pub fn interleaved<F>(ctx: Context<'arena, V>, count: usize, width: usize, var: F) -> Vec<Self>
+      where F: Fn(usize) -> V {
+        return (0..count).map(|i| {
+            let vars: Vec<V> = (0..width).map(|j| var(j * count + i)).collect();
+            BitVec::new(ctx, &vars)
+        }).collect()
+    }
+
+    pub fn width(&self) -> usize {
+        return self.bits.len()
+    }
+
+    pub fn bits(&self) -> &[Bdd<'arena, V>] {
+        return &self.bits
+    }
+
+    // La méthode `eval` calcule la valeur du vecteur pour l'affectation qui
+    // donne à chaque variable `v` la valeur `value(v)`.
+    pub fn eval<F: Fn(V) -> bool>(&self, value: F) -> u64 {
+        return self.bits.iter().enumerate()
+            .map(|(i, b)| (b.eval(&value) as u64) << i)
+            .sum()
+    }
+
+    // La méthode privée `adder` additionne `self`, `other` et la retenue
+    // entrante `carry`.
+    fn adder(&self, other: &Self, mut carry: Bdd<'arena, V>) -> Self {
+        assert_eq!(self.width(), other.width(), "bit vectors of different widths");
+        let bits = self.bits.iter().zip(&other.bits).map(|(&a, &b)| {
+            let axb = a ^ b;
+            let s = axb ^ carry;
+            carry = (a & b) | (carry & axb);
+            s
+        }).collect();
+        return BitVec { ctx: self.ctx, bits: bits }
+    }
+
+    // La méthode `lt` renvoie le BDD de la comparaison non signée
+    // `self < other`.
+    pub fn lt(&self, other: &Self) -> Bdd<'arena, V> {
+        assert_eq!(self.width(), other.width(), "bit vectors of different widths");
+        let mut lt = self.ctx.false_();
+        for (&a, &b) in self.bits.iter().zip(&other.bits) {
+            lt = (!a & b) | (!(a ^ b) & lt)
+        }
+        return lt
+    }
+
+    // La méthode `le` renvoie le BDD de la comparaison `self <= other`.
+    pub fn le(&self, other: &Self) -> Bdd<'arena, V> {
+        return !other.lt(self)
+    }
+
+    // La méthode `equal` renvoie le BDD de l'égalité `self == other`.
+    pub fn equal(&self, other: &Self) -> Bdd<'arena, V> {
+        assert_eq!(self.width(), other.width(), "bit vectors of different widths");
+        return self.bits.iter().zip(&other.bits)
+            .fold(self.ctx.true_(), |acc, (&a, &b)| acc & !(a ^ b))
+    }
+
+    // La fonction `ite` renvoie le vecteur valant `t` si `cond` est vrai, et
+    // `e` sinon.
+    pub fn ite(cond: Bdd<'arena, V>, t: &Self, e: &Self) -> Self {
+        assert_eq!(t.width(), e.width(), "bit vectors of different widths");
+        let bits = t.bits.iter().zip(&e.bits).map(|(&a, &b)| cond.ite(a, b)).collect();
+        return BitVec { ctx: t.ctx, bits: bits }
+    }
+}
+
+// Les opérations arithmétiques sont implémentées en surchargeant les
+// opérateurs Rust correspondants, sur les références de vecteurs.
+
+impl<'arena, V: Hash + Copy + Ord> Add for &BitVec<'arena, V> {
+    type Output = BitVec<'arena, V>;
+    fn add(self, other: Self) -> BitVec<'arena, V> {
+        return self.adder(other, self.ctx.false_())
+    }
+}
+
+// La soustraction calcule `self + !other + 1`.
+impl<'arena, V: Hash + Copy + Ord> Sub for &BitVec<'arena, V> {
+    type Output = BitVec<'arena, V>;
+    fn sub(self, other: Self) -> BitVec<'arena, V> {
+        let not_other = BitVec { ctx: other.ctx, bits: other.bits.iter().map(|&b| !b).collect() };
+        return self.adder(&not_other, self.ctx.true_())
+    }
+}
+
+// La multiplication par une constante additionne les décalages de `self`
+// correspondant aux bits à 1 de la constante.
+impl<'arena, V: Hash + Copy + Ord> Mul<u64> for &BitVec<'arena, V> {
+    type Output = BitVec<'arena, V>;
+    fn mul(self, c: u64) -> BitVec<'arena, V> {
+        let mut res = BitVec::constant(self.ctx, self.width(), 0);
+        for i in 0..self.width().min(64) {
+            if c >> i & 1 == 1 {
+                res = &res + &(self << i)
+            }
+        }
+        return res
+    }
+}
+
+// Décalages logiques, les bits entrants valant 0.
+impl<'arena, V: Hash + Copy + Ord> Shl<usize> for &BitVec<'arena, V> {
+    type Output = BitVec<'arena, V>;
+    fn shl(self, n: usize) -> BitVec<'arena, V> {
+        let w = self.width();
+        let bits = (0..w).map(|i| if i >= n { self.bits[i - n] } else { self.ctx.false_() }).collect();
+        return BitVec { ctx: self.ctx, bits: bits }
+    }
+}
+
+impl<'arena, V: Hash + Copy + Ord> Shr<usize> for &BitVec<'arena, V> {
+    type Output = BitVec<'arena, V>;
+    fn shr(self, n: usize) -> BitVec<'arena, V> {
+        let w = self.width();
+        let bits = (0..w).map(|i| if i + n < w { self.bits[i + n] } else { self.ctx.false_() }).collect();
+        return BitVec { ctx: self.ctx, bits: bits }
+    }
+}
+
+#[test]
+fn test_bitvec_arith() {
+    super::use_bdd(|ctx| {
+        let v = BitVec::interleaved(ctx, 2, 4, |i| i as u8);
+        let (a, b) = (&v[0], &v[1]);
+        let sum = a + b;
+        let diff = a - b;
+        let prod = a * 5;
+        let shl = a << 1;
+        let shr = a >> 3;
+        let lt = a.lt(b);
+        let le = a.le(b);
+        let eq = a.equal(b);
+        let max = BitVec::ite(lt, b, a);
+        for x in 0..16u64 {
+            for y in 0..16u64 {
+                // La variable `i` est le bit `i / 2` de `a` si `i` est pair, de
+                // `b` sinon.
+                let value = |i: u8| if i % 2 == 1 { y >> (i / 2) & 1 == 1 } else { x >> (i / 2) & 1 == 1 };
+                assert_eq!(a.eval(value), x);
+                assert_eq!(sum.eval(value), (x + y) % 16);
+                assert_eq!(diff.eval(value), (x + 16 - y) % 16);
+                assert_eq!(prod.eval(value), x * 5 % 16);
+                assert_eq!(shl.eval(value), x * 2 % 16);
+                assert_eq!(shr.eval(value), x / 8);
+                assert_eq!(lt.eval(value), x < y);
+                assert_eq!(le.eval(value), x <= y);
+                assert_eq!(eq.eval(value), x == y);
+                assert_eq!(max.eval(value), x.max(y));
+            }
+        }
+    });
+}
+
+#[test]
+fn test_bitvec_constraints() {
+    super::use_bdd(|ctx| {
+        // Les solutions de x + y = 10 sur 4 bits, avec x < y.
+        let v = BitVec::interleaved(ctx, 2, 4, |i| i as u8);
+        let ten = BitVec::constant(ctx, 4, 10);
+        let f = (&v[0] + &v[1]).equal(&ten) & v[0].lt(&v[1]);
+        let vars: Vec<u8> = (0..8).collect();
+        // x ∈ {0, ..., 4} avec y = 10 - x, ou x ∈ {11, 12} avec y = 26 - x.
+        assert_eq!(f.nsat(&vars), 7);
+        // L'entrelacement garde l'additionneur de taille linéaire.
+        let v = BitVec::interleaved(ctx, 2, 32, |i| i as u8);
+        let f = (&v[0] + &v[1]).equal(&BitVec::constant(ctx, 32, 12345));
+        assert!(super::raw::topological(&[f.raw]).len() < 32 * 10);
+    });
+}
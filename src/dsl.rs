@@ -0,0 +1,337 @@
+// Un petit langage de formules, sous forme de macros, pour construire des
+// BDDs sans écrire à la main chaque appel aux opérations du contexte.
+//
+// La macro `bdd!(ctx, formule)` construit le BDD de `formule`. `ctx` peut être
+// un contexte de bas niveau (`raw::Context`, ou une référence mutable vers
+// celui-ci), auquel cas le résultat est un `raw::Bdd`, ou un contexte de haut
+// niveau, auquel cas le résultat est un `Bdd`. La syntaxe des formules est :
+//   - les opérateurs `!`, `&`, `^` et `|`, avec les priorités de Rust, et les
+//     parenthèses ;
+//   - les constantes `true` et `false` ;
+//   - `var(v)`, le BDD de la variable `v` ;
+//   - `ite(c, t, e)` ;
+//   - `exists(vars, f)` et `forall(vars, f)`, où `vars` est une expression
+//     Rust énumérant des variables (tableau, vecteur, intervalle...) ;
+//   - `all(f1, ..., fn)` et `any(f1, ..., fn)`, conjonction et disjonction
+//     n-aires, ou `all(for motif in iter => f)` et
+//     `any(for motif in iter => f)`, où `f` peut dépendre du motif ;
+//   - tout identificateur Rust, éventuellement indexé (`x` ou `x[i + 1]`),
+//     désignant un BDD déjà construit, et `{ expr }` pour une expression Rust
+//     quelconque de type BDD.
+// Les macros `all!(ctx, ...)` et `any!(ctx, ...)` sont des raccourcis pour
+// `bdd!(ctx, all(...))` et `bdd!(ctx, any(...))`.
+//
+// Les macros se contentent de transformer la formule en une expression Rust
+// sur le type `Expr` de ce module, qui surcharge les opérateurs en appelant
+// les méthodes du contexte : c'est le compilateur qui se charge des
+// priorités. Ce module n'est public que pour être accessible depuis le code
+// produit par les macros.
+
+use std::cell::RefCell;
+use std::hash::Hash;
+use std::ops::{BitAnd, BitOr, BitXor, Not};
+use super::{raw, Bdd, Context};
+
+// Le trait `Ops` abstrait l'accès au contexte de bas niveau, pour que les
+// mêmes macros servent aux deux interfaces.
+pub trait Ops<'arena, V> {
+    type Out;
+    fn with<T, F: FnOnce(&mut raw::Context<'arena, V>) -> T>(&self, f: F) -> T;
+    fn finish(&self, b: raw::Bdd<'arena, V>) -> Self::Out;
+}
+
+impl<'arena, V> Ops<'arena, V> for RefCell<&mut raw::Context<'arena, V>> {
+    type Out = raw::Bdd<'arena, V>;
+    fn with<T, F: FnOnce(&mut raw::Context<'arena, V>) -> T>(&self, f: F) -> T {
+        return f(&mut self.borrow_mut())
+    }
+    fn finish(&self, b: raw::Bdd<'arena, V>) -> Self::Out {
+        return b
+    }
+}
+
+impl<'arena, V: Hash + Copy + Ord> Ops<'arena, V> for Context<'arena, V> {
+    type Out = Bdd<'arena, V>;
+    fn with<T, F: FnOnce(&mut raw::Context<'arena, V>) -> T>(&self, f: F) -> T {
+        return f(&mut self.0.borrow_mut())
+    }
+    fn finish(&self, b: raw::Bdd<'arena, V>) -> Self::Out {
+        return self.wrap(b)
+    }
+}
+
+// Le trait `IntoOps` convertit l'argument `ctx` des macros en un `Ops`. Il est
+// implémenté pour `&mut raw::Context` plutôt que pour `raw::Context`, de sorte
+// que l'appel de méthode `ctx.into_ops()` emprunte le contexte au lieu de le
+// consommer.
+pub trait IntoOps {
+    type Ops;
+    fn into_ops(self) -> Self::Ops;
+}
+
+impl<'c, 'arena, V> IntoOps for &'c mut raw::Context<'arena, V> {
+    type Ops = RefCell<&'c mut raw::Context<'arena, V>>;
+    fn into_ops(self) -> Self::Ops {
+        return RefCell::new(self)
+    }
+}
+
+impl<'arena, V> IntoOps for Context<'arena, V> {
+    type Ops = Context<'arena, V>;
+    fn into_ops(self) -> Self::Ops {
+        return self
+    }
+}
+
+// Le trait `Leaf` est implémenté par les BDDs des deux interfaces, qui peuvent
+// apparaître comme feuilles des formules.
+pub trait Leaf<'arena, V> {
+    fn into_raw(self) -> raw::Bdd<'arena, V>;
+}
+
+impl<'arena, V> Leaf<'arena, V> for raw::Bdd<'arena, V> {
+    fn into_raw(self) -> raw::Bdd<'arena, V> {
+        return self
+    }
+}
+
+impl<'arena, V> Leaf<'arena, V> for Bdd<'arena, V> {
+    fn into_raw(self) -> raw::Bdd<'arena, V> {
+        return self.raw
+    }
+}
+
+// Une sous-formule en cours de construction.
+pub struct Expr<'c, 'arena, V, C> {
+    ops: &'c C,
+    raw: raw::Bdd<'arena, V>,
+}
+
+impl<'c, 'arena, V: Hash + Copy + Ord, C: Ops<'arena, V>> Expr<'c, 'arena, V, C> {
+    fn apply<F>(self, f: F) -> Self
+      where F: FnOnce(&mut raw::Context<'arena, V>, raw::Bdd<'arena, V>) -> raw::Bdd<'arena, V> {
+        let raw = self.ops.with(|ctx| f(ctx, self.raw));
+        return Expr { ops: self.ops, raw: raw }
+    }
+}
+
+impl<'c, 'arena, V: Hash + Copy + Ord, C: Ops<'arena, V>> Not for Expr<'c, 'arena, V, C> {
+    type Output = Self;
+    fn not(self) -> Self {
+        return self.apply(|ctx, a| ctx.not(a))
+    }
+}
+
+impl<'c, 'arena, V: Hash + Copy + Ord, C: Ops<'arena, V>> BitAnd for Expr<'c, 'arena, V, C> {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self {
+        return self.apply(|ctx, a| ctx.and(a, rhs.raw))
+    }
+}
+
+impl<'c, 'arena, V: Hash + Copy + Ord, C: Ops<'arena, V>> BitOr for Expr<'c, 'arena, V, C> {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        return self.apply(|ctx, a| ctx.or(a, rhs.raw))
+    }
+}
+
+impl<'c, 'arena, V: Hash + Copy + Ord, C: Ops<'arena, V>> BitXor for Expr<'c, 'arena, V, C> {
+    type Output = Self;
+    fn bitxor(self, rhs: Self) -> Self {
+        return self.apply(|ctx, a| ctx.xor(a, rhs.raw))
+    }
+}
+
+// Les fonctions suivantes sont appelées par le code produit par les macros.
+
+pub fn leaf<'c, 'arena, V, C, L: Leaf<'arena, V>>(ops: &'c C, l: L) -> Expr<'c, 'arena, V, C> {
+    return Expr { ops: ops, raw: l.into_raw() }
+}
+
+pub fn constant<'c, 'arena, V, C>(ops: &'c C, b: bool) -> Expr<'c, 'arena, V, C>
+  where V: Hash + Copy + Ord, C: Ops<'arena, V> {
+    let raw = ops.with(|ctx| if b { ctx.true_() } else { ctx.false_() });
+    return Expr { ops: ops, raw: raw }
+}
+
+pub fn var<'c, 'arena, V, C>(ops: &'c C, v: V) -> Expr<'c, 'arena, V, C>
+  where V: Hash + Copy + Ord, C: Ops<'arena, V> {
+    let raw = ops.with(|ctx| ctx.var(v));
+    return Expr { ops: ops, raw: raw }
+}
+
+pub fn ite<'c, 'arena, V, C>([c, t, e]: [Expr<'c, 'arena, V, C>; 3]) -> Expr<'c, 'arena, V, C>
+  where V: Hash + Copy + Ord, C: Ops<'arena, V> {
+    return c.apply(|ctx, c| ctx.ite(c, t.raw, e.raw))
+}
+
+pub fn exists<'c, 'arena, V, C, I>(vars: I, f: Expr<'c, 'arena, V, C>) -> Expr<'c, 'arena, V, C>
+  where V: Hash + Copy + Ord, C: Ops<'arena, V>, I: IntoIterator<Item = V> {
+    let vars: Vec<V> = vars.into_iter().collect();
+    return f.apply(|ctx, f| ctx.exists(f, &vars))
+}
+
+pub fn forall<'c, 'arena, V, C, I>(vars: I, f: Expr<'c, 'arena, V, C>) -> Expr<'c, 'arena, V, C>
+  where V: Hash + Copy + Ord, C: Ops<'arena, V>, I: IntoIterator<Item = V> {
+    let vars: Vec<V> = vars.into_iter().collect();
+    return f.apply(|ctx, f| ctx.forall(f, &vars))
+}
+
+pub fn all<'c, 'arena, V, C, I>(ops: &'c C, fs: I) -> Expr<'c, 'arena, V, C>
+  where V: Hash + Copy + Ord, C: Ops<'arena, V>, I: IntoIterator<Item = Expr<'c, 'arena, V, C>> {
+    return fs.into_iter().fold(constant(ops, true), |acc, f| acc & f)
+}
+
+pub fn any<'c, 'arena, V, C, I>(ops: &'c C, fs: I) -> Expr<'c, 'arena, V, C>
+  where V: Hash + Copy + Ord, C: Ops<'arena, V>, I: IntoIterator<Item = Expr<'c, 'arena, V, C>> {
+    return fs.into_iter().fold(constant(ops, false), |acc, f| acc | f)
+}
+
+pub fn finish<'c, 'arena, V, C: Ops<'arena, V>>(ops: &'c C, e: Expr<'c, 'arena, V, C>) -> C::Out {
+    return ops.finish(e.raw)
+}
+
+#[macro_export]
+macro_rules! bdd {
+    ($ctx:expr, $($f:tt)+) => {{
+        #[allow(unused_imports)]
+        use $crate::dsl::IntoOps as _;
+        let ops = $ctx.into_ops();
+        $crate::dsl::finish(&ops, $crate::__bdd_expr!(ops; []; $($f)+))
+    }};
+}
+
+#[macro_export]
+macro_rules! all {
+    ($ctx:expr, $($f:tt)+) => { $crate::bdd!($ctx, all($($f)+)) };
+}
+
+#[macro_export]
+macro_rules! any {
+    ($ctx:expr, $($f:tt)+) => { $crate::bdd!($ctx, any($($f)+)) };
+}
+
+// La macro `__bdd_expr` parcourt les lexèmes de la formule un par un, en
+// accumulant l'expression Rust produite entre crochets.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bdd_expr {
+    ($ops:ident; [$($acc:tt)*];) => { $($acc)* };
+    ($ops:ident; [$($acc:tt)*]; ! $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)* !]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; & $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)* &]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; | $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)* |]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; ^ $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)* ^]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; true $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)* $crate::dsl::constant(&$ops, true)]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; false $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)* $crate::dsl::constant(&$ops, false)]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; var ($v:expr) $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)* $crate::dsl::var(&$ops, $v)]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; ite ($($args:tt)*) $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)*
+            $crate::dsl::ite($crate::__bdd_list!($ops; []; []; $($args)*))]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; exists ($vars:expr, $($f:tt)+) $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)*
+            $crate::dsl::exists($vars, $crate::__bdd_expr!($ops; []; $($f)+))]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; forall ($vars:expr, $($f:tt)+) $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)*
+            $crate::dsl::forall($vars, $crate::__bdd_expr!($ops; []; $($f)+))]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; all (for $p:pat in $it:expr => $($f:tt)+) $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)*
+            $crate::dsl::all(&$ops, ($it).into_iter().map(|$p| $crate::__bdd_expr!($ops; []; $($f)+)))];
+            $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; any (for $p:pat in $it:expr => $($f:tt)+) $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)*
+            $crate::dsl::any(&$ops, ($it).into_iter().map(|$p| $crate::__bdd_expr!($ops; []; $($f)+)))];
+            $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; all ($($args:tt)*) $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)*
+            $crate::dsl::all(&$ops, $crate::__bdd_list!($ops; []; []; $($args)*))]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; any ($($args:tt)*) $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)*
+            $crate::dsl::any(&$ops, $crate::__bdd_list!($ops; []; []; $($args)*))]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; ($($f:tt)+) $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)* ($crate::__bdd_expr!($ops; []; $($f)+))]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; {$e:expr} $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)* $crate::dsl::leaf(&$ops, $e)]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; $x:ident [$($i:tt)*] $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)* $crate::dsl::leaf(&$ops, $x[$($i)*])]; $($rest)*)
+    };
+    ($ops:ident; [$($acc:tt)*]; $x:ident $($rest:tt)*) => {
+        $crate::__bdd_expr!($ops; [$($acc)* $crate::dsl::leaf(&$ops, $x)]; $($rest)*)
+    };
+}
+
+// La macro `__bdd_list` découpe une liste de formules séparées par des
+// virgules, et produit le tableau des expressions correspondantes.
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __bdd_list {
+    ($ops:ident; [$($done:expr),*]; [$($cur:tt)+];) => {
+        [$($done,)* $crate::__bdd_expr!($ops; []; $($cur)+)]
+    };
+    ($ops:ident; [$($done:expr),*]; [$($cur:tt)+]; , $($rest:tt)*) => {
+        $crate::__bdd_list!($ops; [$($done,)* $crate::__bdd_expr!($ops; []; $($cur)+)]; []; $($rest)*)
+    };
+    ($ops:ident; [$($done:expr),*]; [$($cur:tt)*]; $t:tt $($rest:tt)*) => {
+        $crate::__bdd_list!($ops; [$($done),*]; [$($cur)* $t]; $($rest)*)
+    };
+}
+
+#[test]
+fn test_bdd_macro_raw() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = raw::Context::new(&allo);
+    let x: Vec<raw::Bdd<u8>> = (0..4).map(|i| ctx.var(i)).collect();
+
+    let f = bdd!(ctx, (x[0] & !x[1]) | (x[2] ^ x[3]));
+    let nx1 = ctx.not(x[1]);
+    let a = ctx.and(x[0], nx1);
+    let b = ctx.xor(x[2], x[3]);
+    assert_eq!(f, ctx.or(a, b));
+
+    // Le contexte peut aussi être une référence mutable.
+    let r = &mut ctx;
+    let g = bdd!(r, !x[0] & x[1] | x[2]);
+    let h = bdd!(r, ((!x[0]) & x[1]) | x[2]);
+    assert_eq!(g, h);
+    assert_eq!(bdd!(r, ite(x[0], true, var(1)) ^ (x[0] | x[1])), bdd!(r, false));
+    assert_eq!(bdd!(r, exists([0, 1], x[0] & x[1] & x[2])), x[2]);
+    assert_eq!(bdd!(r, forall(0..2, x[0] | x[1] | {g})), bdd!(r, x[2]));
+}
+
+#[test]
+fn test_bdd_macro() {
+    super::use_bdd(|ctx| {
+        let x: Vec<Bdd<u8>> = (0..4).map(|i| ctx.var(i)).collect();
+        let (a, b) = (x[0], x[1]);
+        assert_eq!(bdd!(ctx, !a & b | x[2]), (!a & b) | x[2]);
+        assert_eq!(bdd!(ctx, all(a, b, !x[2])), a & b & !x[2]);
+        assert_eq!(all!(ctx, for i in 0..4 => !x[i]), !x[0] & !x[1] & !x[2] & !x[3]);
+        assert_eq!(any!(ctx, for &y in &x[1..] => y & a), a & (x[1] | x[2] | x[3]));
+        assert_eq!(bdd!(ctx, any(for i in 0..0 => x[i])), ctx.false_());
+        assert_eq!(bdd!(ctx, ite(a, b, x[2])), a.ite(b, x[2]));
+    });
+}
+
@@ -1,8 +1,8 @@
-// Ces deux annotations permettent de désactiver des avertissements produits par
-// compilateur Rust, dus au fait que le code est incomplet dans cette version.
-// Elles devront être retirées lorsque les trous seront complétés.
-#![allow(unused_variables)]
-#![allow(dead_code)]
+// Le code de `raw.rs` utilise volontairement des `return` explicites et des
+// initialisations de champs de la forme `alloc: alloc`. On désactive donc les
+// avertissements de clippy correspondants.
+#![allow(clippy::needless_return)]
+#![allow(clippy::redundant_field_names)]
 
 // Le module `raw`, offrant une interface de bas niveau à la bibliothèque
 // de BDD, est un sous-module du module principal de la bibliothèque.
@@ -12,6 +12,26 @@
 // dans `raw`.
 pub mod raw;
 
+// Le module `dsl` contient les définitions utilisées par les macros `bdd!`,
+// `all!` et `any!`, qui permettent d'écrire des formules dans une syntaxe
+// proche de celle de Rust, pour les deux interfaces.
+#[macro_use]
+pub mod dsl;
+
+// Les fonctionnalités annexes de l'interface de haut niveau sont réparties dans
+// des sous-modules privés, dont on ré-exporte les types publics.
+mod parser;
+mod bitvec;
+mod bddset;
+mod relation;
+
+pub use parser::ParseError;
+pub use bitvec::BitVec;
+pub use bddset::BddSet;
+pub use relation::BddRelation;
+pub use raw::Cover;
+pub use raw::NodeView;
+
 // L'interface de haut niveau permet de palier à plusieurs défauts de
 // l'interface bas-niveau :
 //    - Il faut spécifier explicitement le contexte lors de chaque opération
@@ -47,14 +67,20 @@ pub mod raw;
 
 use std::ops::*;
 use std::hash::Hash;
+use std::cell::RefCell;
 
+// Le contexte de haut niveau n'est qu'un emprunt partagé vers le contexte de
+// bas niveau. Celui-ci est placé dans une `RefCell`, afin de pouvoir
+// l'emprunter mutablement lors de chaque opération.
 #[derive(Copy, Clone)]
-pub struct Context<'arena, V> (&'arena V /* À CHANGER */);
+pub struct Context<'arena, V> (&'arena RefCell<raw::Context<'arena, V>>);
 
+// Un BDD de haut niveau est un BDD de bas niveau, accompagné du contexte qui
+// a permis de le créer.
 #[derive(Copy, Clone)]
 pub struct Bdd<'arena, V> {
     raw: raw::Bdd<'arena, V>,
-    // À COMPLÉTER
+    ctx: Context<'arena, V>,
 }
 
 // La fonction `use_bdd` crée une nouvelle arène d'allocation et un nouveau
@@ -64,8 +90,9 @@ pub struct Bdd<'arena, V> {
 // elle identifie uniquement le contexte.
 pub fn use_bdd<V: Hash + Copy + Ord, T, F>(f: F) -> T
   where F: for<'arena> FnOnce(Context<'arena, V>) -> T {
-    // À COMPLÉTER
-    panic!()
+    let alloc = bumpalo::Bump::new();
+    let ctx = RefCell::new(raw::Context::new(&alloc));
+    return f(Context(&ctx))
 }
 
 
@@ -74,19 +101,65 @@ pub fn use_bdd<V: Hash + Copy + Ord, T, F>(f: F) -> T
 // d'autres BDD en paramètre: les formules constantes VRAI et FAUX et les
 // atomes.
 impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
+    // La méthode privée `wrap` associe un BDD de bas niveau à ce contexte.
+    fn wrap(self, raw: raw::Bdd<'arena, V>) -> Bdd<'arena, V> {
+        return Bdd { raw: raw, ctx: self }
+    }
+
     pub fn true_(self) -> Bdd<'arena, V> {
-        // À COMPLÉTER
-        panic!()
+        let raw = self.0.borrow_mut().true_();
+        return self.wrap(raw)
     }
 
     pub fn false_(self) -> Bdd<'arena, V> {
-        // À COMPLÉTER
-        panic!()
+        let raw = self.0.borrow_mut().false_();
+        return self.wrap(raw)
     }
 
     pub fn var(self, x: V) -> Bdd<'arena, V> {
-        // À COMPLÉTER
-        panic!()
+        let raw = self.0.borrow_mut().var(x);
+        return self.wrap(raw)
+    }
+
+    // La méthode `from_truth_table` construit un BDD à partir de sa table de
+    // vérité (voir le module `raw::truth`).
+    pub fn from_truth_table(self, vars: &[V], bits: &[bool]) -> Bdd<'arena, V> {
+        let raw = self.0.borrow_mut().from_truth_table(vars, bits);
+        return self.wrap(raw)
+    }
+
+    // Les méthodes `at_most`, `at_least` et `exactly` construisent des
+    // contraintes de cardinalité (voir le module `raw::cardinality`).
+    pub fn at_most(self, k: usize, vars: &[V]) -> Bdd<'arena, V> {
+        let raw = self.0.borrow_mut().at_most(k, vars);
+        return self.wrap(raw)
+    }
+
+    pub fn at_least(self, k: usize, vars: &[V]) -> Bdd<'arena, V> {
+        let raw = self.0.borrow_mut().at_least(k, vars);
+        return self.wrap(raw)
+    }
+
+    pub fn exactly(self, k: usize, vars: &[V]) -> Bdd<'arena, V> {
+        let raw = self.0.borrow_mut().exactly(k, vars);
+        return self.wrap(raw)
+    }
+
+    // Les méthodes `linear_le`, `linear_ge` et `linear_eq` construisent des
+    // contraintes pseudo-booléennes linéaires (voir le module `raw::linear`).
+    pub fn linear_le(self, terms: &[(i64, V)], bound: i64) -> Bdd<'arena, V> {
+        let raw = self.0.borrow_mut().linear_le(terms, bound);
+        return self.wrap(raw)
+    }
+
+    pub fn linear_ge(self, terms: &[(i64, V)], bound: i64) -> Bdd<'arena, V> {
+        let raw = self.0.borrow_mut().linear_ge(terms, bound);
+        return self.wrap(raw)
+    }
+
+    pub fn linear_eq(self, terms: &[(i64, V)], bound: i64) -> Bdd<'arena, V> {
+        let raw = self.0.borrow_mut().linear_eq(terms, bound);
+        return self.wrap(raw)
     }
 }
 
@@ -95,62 +168,77 @@ impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
 
 impl<'arena, V> PartialEq<Bdd<'arena, V>> for Bdd<'arena, V> {
     fn eq(&self, x: &Bdd<'arena, V>) -> bool {
-        // À COMPLÉTER
-        panic!()
+        return self.raw == x.raw
     }
 }
 impl<'arena, V> Eq for Bdd<'arena, V> { }
+impl<'arena, V> Hash for Bdd<'arena, V> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.raw.hash(state)
+    }
+}
 
 impl<'arena, V: Hash + Copy + Ord> Not for Bdd<'arena, V> {
     type Output = Bdd<'arena, V>;
     fn not(self) -> Bdd<'arena, V> {
-        // À COMPLÉTER
-        panic!()
+        let raw = self.ctx.0.borrow_mut().not(self.raw);
+        return self.ctx.wrap(raw)
     }
 }
 
 impl<'arena, V: Hash + Copy + Ord> BitAnd for Bdd<'arena, V> {
     type Output = Bdd<'arena, V>;
     fn bitand(self, rhs: Bdd<'arena, V>) -> Bdd<'arena, V> {
-        // À COMPLÉTER
-        panic!()
+        let raw = self.ctx.0.borrow_mut().and(self.raw, rhs.raw);
+        return self.ctx.wrap(raw)
     }
 }
 
 impl<'arena, V: Hash + Copy + Ord> BitAndAssign for Bdd<'arena, V> {
     fn bitand_assign(&mut self, rhs: Bdd<'arena, V>) {
-        // À COMPLÉTER
-        panic!()
+        *self = *self & rhs
     }
 }
 
 impl<'arena, V: Hash + Copy + Ord> BitOr for Bdd<'arena, V> {
     type Output = Bdd<'arena, V>;
     fn bitor(self, rhs: Bdd<'arena, V>) -> Bdd<'arena, V> {
-        // À COMPLÉTER
-        panic!()
+        let raw = self.ctx.0.borrow_mut().or(self.raw, rhs.raw);
+        return self.ctx.wrap(raw)
     }
 }
 
 impl<'arena, V: Hash + Copy + Ord> BitOrAssign for Bdd<'arena, V> {
     fn bitor_assign(&mut self, rhs: Bdd<'arena, V>) {
-        // À COMPLÉTER
-        panic!()
+        *self = *self | rhs
     }
 }
 
 impl<'arena, V: Hash + Copy + Ord> BitXor for Bdd<'arena, V> {
     type Output = Bdd<'arena, V>;
     fn bitxor(self, rhs: Bdd<'arena, V>) -> Bdd<'arena, V> {
-        // À COMPLÉTER
-        panic!()
+        let raw = self.ctx.0.borrow_mut().xor(self.raw, rhs.raw);
+        return self.ctx.wrap(raw)
     }
 }
 
 impl<'arena, V: Hash + Copy + Ord> BitXorAssign for Bdd<'arena, V> {
     fn bitxor_assign(&mut self, rhs: Bdd<'arena, V>) {
-        // À COMPLÉTER
-        panic!()
+        *self = *self ^ rhs
+    }
+}
+
+// L'affichage d'un BDD de haut niveau est celui du BDD de bas niveau
+// correspondant (voir le module `raw::print`).
+impl<'arena, V: Copy + std::fmt::Debug> std::fmt::Debug for Bdd<'arena, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.raw.fmt(f)
+    }
+}
+
+impl<'arena, V: Copy + std::fmt::Display> std::fmt::Display for Bdd<'arena, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.raw.fmt(f)
     }
 }
 
@@ -158,8 +246,183 @@ impl<'arena, V: Hash + Copy + Ord> BitXorAssign for Bdd<'arena, V> {
 // bas niveau.
 impl<'arena, V: Hash + Copy + Ord> Bdd<'arena, V> {
     pub fn nsat(self, vars: &[V]) -> u64 {
-        // À COMPLÉTER
-        panic!()
+        return self.raw.nsat(vars)
+    }
+
+    // La méthode `ite` renvoie le BDD de « si `self` alors `t` sinon `e` ».
+    pub fn ite(self, t: Bdd<'arena, V>, e: Bdd<'arena, V>) -> Bdd<'arena, V> {
+        let raw = self.ctx.0.borrow_mut().ite(self.raw, t.raw, e.raw);
+        return self.ctx.wrap(raw)
+    }
+
+    // Les méthodes `exists` et `forall` quantifient le BDD sur les variables
+    // de `vars`.
+    pub fn exists(self, vars: &[V]) -> Bdd<'arena, V> {
+        let raw = self.ctx.0.borrow_mut().exists(self.raw, vars);
+        return self.ctx.wrap(raw)
+    }
+
+    pub fn forall(self, vars: &[V]) -> Bdd<'arena, V> {
+        let raw = self.ctx.0.borrow_mut().forall(self.raw, vars);
+        return self.ctx.wrap(raw)
+    }
+
+    // Les méthodes `view`, `top_var`, `low`, `high`, `is_true`, `is_false` et
+    // `fold` inspectent la structure du BDD (voir le module `raw::inspect`).
+    pub fn view(self) -> NodeView<V, Bdd<'arena, V>> {
+        match self.raw.view() {
+            NodeView::False => NodeView::False,
+            NodeView::True => NodeView::True,
+            NodeView::If { var, low, high } =>
+                NodeView::If { var: var, low: self.ctx.wrap(low), high: self.ctx.wrap(high) }
+        }
+    }
+
+    pub fn top_var(self) -> Option<V> {
+        return self.raw.top_var()
+    }
+
+    pub fn low(self) -> Option<Bdd<'arena, V>> {
+        return self.raw.low().map(|b| self.ctx.wrap(b))
+    }
+
+    pub fn high(self) -> Option<Bdd<'arena, V>> {
+        return self.raw.high().map(|b| self.ctx.wrap(b))
+    }
+
+    pub fn is_true(self) -> bool {
+        return self.raw.is_true()
+    }
+
+    pub fn is_false(self) -> bool {
+        return self.raw.is_false()
+    }
+
+    pub fn fold<T, L, N>(self, leaf: L, node: N) -> T
+      where T: Clone, L: Fn(bool) -> T, N: Fn(V, &T, &T) -> T {
+        return self.raw.fold(leaf, node)
+    }
+
+    // Les méthodes `cofactors`, `boolean_difference`, `is_sensitive_to`,
+    // `influence` et `influences` interrogent la dépendance du BDD à ses
+    // variables (voir le module `raw::sensitivity`).
+    pub fn cofactors(self, v: V) -> (Bdd<'arena, V>, Bdd<'arena, V>) {
+        let (f0, f1) = self.ctx.0.borrow_mut().cofactors(self.raw, v);
+        return (self.ctx.wrap(f0), self.ctx.wrap(f1))
+    }
+
+    pub fn boolean_difference(self, v: V) -> Bdd<'arena, V> {
+        let raw = self.ctx.0.borrow_mut().boolean_difference(self.raw, v);
+        return self.ctx.wrap(raw)
+    }
+
+    pub fn is_sensitive_to(self, v: V) -> bool {
+        return self.raw.is_sensitive_to(v)
+    }
+
+    pub fn influence(self, v: V) -> f64 {
+        return self.ctx.0.borrow_mut().influence(self.raw, v)
+    }
+
+    pub fn influences(self) -> Vec<(V, f64)> {
+        return self.ctx.0.borrow_mut().influences(self.raw)
+    }
+
+    // La méthode `rename` remplace chaque variable `v` du BDD par `map(v)`
+    // (voir `raw::Context::rename`).
+    pub fn rename<F: Fn(V) -> V>(self, map: F) -> Bdd<'arena, V> {
+        let raw = self.ctx.0.borrow_mut().rename(self.raw, map);
+        return self.ctx.wrap(raw)
+    }
+
+    // Les méthodes `isop` et `isop_interval` calculent des couvertures
+    // irredondantes en somme de produits (voir le module `raw::isop`).
+    pub fn isop(self) -> Cover<V> {
+        return self.ctx.0.borrow_mut().isop(self.raw)
+    }
+
+    pub fn isop_interval(lower: Bdd<'arena, V>, upper: Bdd<'arena, V>) -> (Cover<V>, Bdd<'arena, V>) {
+        let (cover, f) = lower.ctx.0.borrow_mut().isop_interval(lower.raw, upper.raw);
+        return (cover, lower.ctx.wrap(f))
+    }
+
+    // Les méthodes `to_rust` et `to_c` compilent le BDD en une fonction Rust
+    // ou C autonome (voir le module `raw::codegen`).
+    pub fn to_rust<F: Fn(V) -> usize>(self, name: &str, index: F) -> String {
+        return self.raw.to_rust(name, index)
+    }
+
+    pub fn to_c<F: Fn(V) -> usize>(self, name: &str, index: F) -> String {
+        return self.raw.to_c(name, index)
+    }
+
+    // Les méthodes suivantes sont des versions de haut niveau des méthodes
+    // du module `raw::truth`.
+    pub fn eval<F: Fn(V) -> bool>(self, value: F) -> bool {
+        return self.raw.eval(value)
+    }
+
+    pub fn to_truth_table(self, vars: &[V]) -> Vec<bool> {
+        return self.raw.to_truth_table(vars)
+    }
+
+    pub fn render_truth_table(self, vars: &[V]) -> String
+      where V: std::fmt::Display {
+        return self.raw.render_truth_table(vars)
+    }
+
+    pub fn render_karnaugh(self, vars: &[V]) -> String
+      where V: std::fmt::Display {
+        return self.raw.render_karnaugh(vars)
+    }
+}
+
+// La fonction privée `raws` extrait les BDDs de bas niveau d'une liste de BDDs
+// de haut niveau, pour les opérations portant sur plusieurs racines.
+fn raws<'arena, V: Copy>(roots: &[Bdd<'arena, V>]) -> Vec<raw::Bdd<'arena, V>> {
+    return roots.iter().map(|b| b.raw).collect()
+}
+
+// La méthode `to_dot` est une version de haut niveau de la méthode `to_dot` de
+// bas niveau.
+impl<'arena, V: Hash + Copy + Ord + std::fmt::Display> Context<'arena, V> {
+    pub fn to_dot(self, roots: &[Bdd<'arena, V>], names: &[&str]) -> String {
+        return self.0.borrow().to_dot(&raws(roots), names)
+    }
+}
+
+// Les méthodes `save` et `load` sont des versions de haut niveau des méthodes
+// correspondantes de bas niveau (voir le module `raw::binary`).
+impl<'arena, V: Hash + Copy + Ord + raw::BinaryVar> Context<'arena, V> {
+    pub fn save<W: std::io::Write>(self, w: &mut W, roots: &[(&str, Bdd<'arena, V>)])
+                                   -> std::io::Result<()> {
+        let roots: Vec<_> = roots.iter().map(|&(name, b)| (name, b.raw)).collect();
+        return self.0.borrow().save(w, &roots)
+    }
+
+    pub fn load<R: std::io::Read>(self, r: &mut R)
+                                  -> std::io::Result<Vec<(String, Bdd<'arena, V>)>> {
+        let roots = self.0.borrow_mut().load(r)?;
+        return Ok(roots.into_iter().map(|(name, b)| (name, self.wrap(b))).collect())
+    }
+}
+
+// Les méthodes `to_json` et `from_json` encodent et décodent un ensemble de
+// BDDs nommés au format JSON (voir le module `raw::json`). Elles ne sont
+// disponibles qu'avec la fonctionnalité `json`.
+#[cfg(feature = "json")]
+impl<'arena, V> Context<'arena, V>
+  where V: Hash + Copy + Ord + serde::Serialize + serde::de::DeserializeOwned {
+    pub fn to_json(self, roots: &[(&str, Bdd<'arena, V>)]) -> String {
+        let roots: Vec<_> = roots.iter().map(|&(name, b)| (name, b.raw)).collect();
+        return self.0.borrow().to_json(&roots).to_string()
+    }
+
+    pub fn from_json(self, json: &str)
+                     -> Result<Vec<(String, Bdd<'arena, V>)>, serde_json::Error> {
+        let json = serde_json::from_str(json)?;
+        let roots = self.0.borrow_mut().from_json(&json)?;
+        return Ok(roots.into_iter().map(|(name, b)| (name, self.wrap(b))).collect())
     }
 }
 
@@ -206,3 +469,93 @@ fn test() {
         assert!(x ^ y == (x & !y) | (!x & y));
     });
 }
+
+#[test]
+fn test_fmt() {
+    use_bdd(|ctx| {
+        let x = ctx.var(0);
+        let y = ctx.var(1);
+        assert_eq!(format!("{:?}", x & y),
+                   "Bdd(n0 = if x1 then true else false; n1 = if x0 then n0 else false; n1)");
+        assert_eq!((x | !y).to_string(), "x0 | !x1");
+        assert_eq!(x & !x, ctx.false_());
+    });
+}
+
+#[test]
+fn test_save_load() {
+    let mut buf = Vec::new();
+    use_bdd(|ctx| {
+        let x = ctx.var(0u16);
+        let y = ctx.var(1);
+        ctx.save(&mut buf, &[("xy", x & y), ("nx", !x)]).unwrap();
+    });
+    use_bdd(|ctx| {
+        let roots = ctx.load(&mut &buf[..]).unwrap();
+        assert_eq!(roots, vec![("xy".to_string(), ctx.var(0u16) & ctx.var(1)),
+                               ("nx".to_string(), !ctx.var(0))]);
+    });
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_json() {
+    let json = use_bdd(|ctx| {
+        let x = ctx.var(0u16);
+        let y = ctx.var(1);
+        ctx.to_json(&[("f", x ^ y)])
+    });
+    use_bdd(|ctx| {
+        let roots = ctx.from_json(&json).unwrap();
+        assert_eq!(roots, vec![("f".to_string(), ctx.var(0u16) ^ ctx.var(1))]);
+        assert!(ctx.from_json("{").is_err());
+    });
+}
+
+#[test]
+fn test_isop() {
+    use_bdd(|ctx| {
+        let x = ctx.var(0);
+        let y = ctx.var(1);
+        let z = ctx.var(2);
+        assert_eq!(((x & y) | (x & !y & z)).isop().to_string(), "x0 & x1 | x0 & x2");
+        let (cover, f) = Bdd::isop_interval(x & y, x);
+        assert_eq!(cover.to_string(), "x0");
+        assert_eq!(f, x);
+    });
+}
+
+#[test]
+fn test_to_dot() {
+    use_bdd(|ctx| {
+        let x = ctx.var(0);
+        let y = ctx.var(1);
+        let dot = ctx.to_dot(&[x & y, x | y], &["f", "g"]);
+        assert_eq!(dot.matches("shape=circle").count(), 3);
+        assert!(dot.contains("label=\"g\""));
+    });
+}
+
+#[test]
+fn test_cardinality() {
+    // Le problème des pigeons, avec des contraintes de cardinalité : n + 1
+    // pigeons ne tiennent pas dans n trous s'il y a au plus un pigeon par trou.
+    let pigeonhole = |pigeons: u16, holes: u16| use_bdd(|ctx| {
+        let mut f = ctx.true_();
+        for p in 0..pigeons {
+            let vars: Vec<u16> = (0..holes).map(|h| p + h * pigeons).collect();
+            f &= ctx.at_least(1, &vars)
+        }
+        for h in 0..holes {
+            let vars: Vec<u16> = (0..pigeons).map(|p| p + h * pigeons).collect();
+            f &= ctx.at_most(1, &vars)
+        }
+        f != ctx.false_()
+    });
+    assert!(!pigeonhole(6, 5));
+    assert!(pigeonhole(5, 5));
+    use_bdd(|ctx| {
+        let vars = [0u8, 1, 2];
+        assert_eq!(ctx.exactly(2, &vars).nsat(&vars), 3);
+    });
+}
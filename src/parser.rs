@@ -0,0 +1,316 @@
+// Analyseur syntaxique de formules booléennes.
+//
+// Ce module permet de construire un BDD à partir d'une formule écrite sous
+// forme textuelle, plutôt qu'en code Rust. La grammaire des formules est la
+// suivante, par priorité croissante :
+//     formule ::= formule '<->' formule      (équivalence, associative à gauche)
+//              |  formule '->' formule       (implication, associative à droite)
+//              |  formule '|' formule
+//              |  formule '^' formule
+//              |  formule '&' formule
+//              |  '!' formule
+//              |  '(' formule ')'
+//              |  'true' | 'false' | '1' | '0'
+//              |  'ite' '(' formule ',' formule ',' formule ')'
+//              |  'and' '(' formule, ... ')' | 'or' '(' formule, ... ')'
+//              |  variable
+// Les noms de variables sont formés de lettres, de chiffres et des caractères
+// `_` et `.`, et ne commencent pas par un chiffre. `and`, `or` et `ite` ne sont
+// des mots-clés que lorsqu'ils sont suivis d'une parenthèse ouvrante.
+
+use std::fmt;
+use std::hash::Hash;
+use super::{Bdd, Context};
+
+// Une erreur de syntaxe, avec sa position (ligne et colonne, à partir de 1)
+// dans le texte analysé.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for ParseError { }
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Const(bool),
+    LParen, RParen, Comma,
+    Not, And, Or, Xor, Imp, Iff,
+    Eof,
+}
+
+// Un lexème, avec sa position dans le texte.
+struct Lexeme {
+    token: Token,
+    line: usize,
+    column: usize,
+}
+
+// La fonction `lex` découpe le texte en lexèmes. Le dernier lexème est
+// toujours `Token::Eof`.
+fn lex(text: &str) -> Result<Vec<Lexeme>, ParseError> {
+    let mut res = Vec::new();
+    let mut chars = text.chars().peekable();
+    let (mut line, mut column) = (1, 1);
+    while let Some(&c) = chars.peek() {
+        let (l, col) = (line, column);
+        let mut advance = |chars: &mut std::iter::Peekable<std::str::Chars>| {
+            let c = chars.next().unwrap();
+            if c == '\n' {
+                line += 1;
+                column = 1
+            } else {
+                column += 1
+            }
+            c
+        };
+        let token = match c {
+            _ if c.is_whitespace() => { advance(&mut chars); continue }
+            '(' => { advance(&mut chars); Token::LParen }
+            ')' => { advance(&mut chars); Token::RParen }
+            ',' => { advance(&mut chars); Token::Comma }
+            '!' => { advance(&mut chars); Token::Not }
+            '&' => { advance(&mut chars); Token::And }
+            '|' => { advance(&mut chars); Token::Or }
+            '^' => { advance(&mut chars); Token::Xor }
+            '-' | '<' => {
+                let expected = if c == '-' { "->" } else { "<->" };
+                for e in expected.chars() {
+                    if chars.peek() != Some(&e) {
+                        return Err(ParseError { line: l, column: col,
+                                                message: format!("expected `{}`", expected) })
+                    }
+                    advance(&mut chars);
+                }
+                if c == '-' { Token::Imp } else { Token::Iff }
+            }
+            _ if c.is_alphanumeric() || c == '_' => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_' || c == '.') {
+                        break
+                    }
+                    word.push(advance(&mut chars))
+                }
+                match word.as_str() {
+                    "true" | "1" => Token::Const(true),
+                    "false" | "0" => Token::Const(false),
+                    _ if word.starts_with(|c: char| c.is_ascii_digit()) =>
+                        return Err(ParseError { line: l, column: col,
+                                                message: format!("invalid identifier `{}`", word) }),
+                    _ => Token::Ident(word)
+                }
+            }
+            _ => return Err(ParseError { line: l, column: col,
+                                         message: format!("unexpected character `{}`", c) })
+        };
+        res.push(Lexeme { token, line: l, column: col })
+    }
+    res.push(Lexeme { token: Token::Eof, line, column });
+    return Ok(res)
+}
+
+struct Parser<'a, 'arena, V, F> {
+    ctx: Context<'arena, V>,
+    var: &'a mut F,
+    lexemes: Vec<Lexeme>,
+    pos: usize,
+}
+
+impl<'a, 'arena, V: Hash + Copy + Ord, F: FnMut(&str) -> Option<V>> Parser<'a, 'arena, V, F> {
+    fn peek(&self) -> &Token {
+        return &self.lexemes[self.pos].token
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, ParseError> {
+        let l = &self.lexemes[self.pos];
+        return Err(ParseError { line: l.line, column: l.column, message })
+    }
+
+    fn expect(&mut self, token: Token, what: &str) -> Result<(), ParseError> {
+        if *self.peek() != token {
+            return self.error(format!("expected {}", what))
+        }
+        self.pos += 1;
+        return Ok(())
+    }
+
+    fn iff(&mut self) -> Result<Bdd<'arena, V>, ParseError> {
+        let mut res = self.imp()?;
+        while *self.peek() == Token::Iff {
+            self.pos += 1;
+            res = !(res ^ self.imp()?)
+        }
+        return Ok(res)
+    }
+
+    fn imp(&mut self) -> Result<Bdd<'arena, V>, ParseError> {
+        let lhs = self.or()?;
+        if *self.peek() == Token::Imp {
+            self.pos += 1;
+            return Ok(!lhs | self.imp()?)
+        }
+        return Ok(lhs)
+    }
+
+    fn or(&mut self) -> Result<Bdd<'arena, V>, ParseError> {
+        let mut res = self.xor()?;
+        while *self.peek() == Token::Or {
+            self.pos += 1;
+            res |= self.xor()?
+        }
+        return Ok(res)
+    }
+
+    fn xor(&mut self) -> Result<Bdd<'arena, V>, ParseError> {
+        let mut res = self.and()?;
+        while *self.peek() == Token::Xor {
+            self.pos += 1;
+            res ^= self.and()?
+        }
+        return Ok(res)
+    }
+
+    fn and(&mut self) -> Result<Bdd<'arena, V>, ParseError> {
+        let mut res = self.unary()?;
+        while *self.peek() == Token::And {
+            self.pos += 1;
+            res &= self.unary()?
+        }
+        return Ok(res)
+    }
+
+    fn unary(&mut self) -> Result<Bdd<'arena, V>, ParseError> {
+        if *self.peek() == Token::Not {
+            self.pos += 1;
+            return Ok(!self.unary()?)
+        }
+        return self.atom()
+    }
+
+    // La méthode `args` lit une liste non vide de formules séparées par des
+    // virgules et entourée de parenthèses.
+    fn args(&mut self) -> Result<Vec<Bdd<'arena, V>>, ParseError> {
+        self.expect(Token::LParen, "`(`")?;
+        let mut res = vec![self.iff()?];
+        while *self.peek() == Token::Comma {
+            self.pos += 1;
+            res.push(self.iff()?)
+        }
+        self.expect(Token::RParen, "`)`")?;
+        return Ok(res)
+    }
+
+    fn atom(&mut self) -> Result<Bdd<'arena, V>, ParseError> {
+        let is_call = self.lexemes.get(self.pos + 1).is_some_and(|l| l.token == Token::LParen);
+        match self.peek().clone() {
+            Token::LParen => {
+                self.pos += 1;
+                let res = self.iff()?;
+                self.expect(Token::RParen, "`)`")?;
+                return Ok(res)
+            }
+            Token::Const(b) => {
+                self.pos += 1;
+                return Ok(if b { self.ctx.true_() } else { self.ctx.false_() })
+            }
+            Token::Ident(name) if is_call && name == "ite" => {
+                let start = self.pos;
+                self.pos += 1;
+                let args = self.args()?;
+                if args.len() != 3 {
+                    self.pos = start;
+                    return self.error("`ite` expects 3 arguments".to_string())
+                }
+                return Ok((args[0] & args[1]) | (!args[0] & args[2]))
+            }
+            Token::Ident(name) if is_call && name == "and" => {
+                self.pos += 1;
+                return Ok(self.args()?.into_iter().fold(self.ctx.true_(), |acc, b| acc & b))
+            }
+            Token::Ident(name) if is_call && name == "or" => {
+                self.pos += 1;
+                return Ok(self.args()?.into_iter().fold(self.ctx.false_(), |acc, b| acc | b))
+            }
+            Token::Ident(name) => {
+                match (self.var)(&name) {
+                    Some(v) => {
+                        self.pos += 1;
+                        return Ok(self.ctx.var(v))
+                    }
+                    None => return self.error(format!("unknown variable `{}`", name))
+                }
+            }
+            Token::Eof => return self.error("unexpected end of input".to_string()),
+            _ => return self.error("expected a formula".to_string())
+        }
+    }
+}
+
+impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
+    // La méthode `parse` construit le BDD de la formule `text`. La fonction
+    // `var` associe à chaque nom de variable la variable correspondante, ou
+    // `None` si ce nom est inconnu, ce qui produit une erreur.
+    pub fn parse<F>(self, text: &str, mut var: F) -> Result<Bdd<'arena, V>, ParseError>
+      where F: FnMut(&str) -> Option<V> {
+        let mut parser = Parser { ctx: self, var: &mut var, lexemes: lex(text)?, pos: 0 };
+        let res = parser.iff()?;
+        if *parser.peek() != Token::Eof {
+            return parser.error("expected an operator".to_string())
+        }
+        return Ok(res)
+    }
+}
+
+#[test]
+fn test_parse() {
+    super::use_bdd(|ctx| {
+        let names = ["a", "b", "c"];
+        let var = |s: &str| names.iter().position(|&n| n == s).map(|i| i as u8);
+        let a = ctx.var(0);
+        let b = ctx.var(1);
+        let c = ctx.var(2);
+        let p = |s: &str| ctx.parse(s, var).unwrap();
+
+        assert_eq!(p("a"), a);
+        assert_eq!(p("true"), ctx.true_());
+        assert_eq!(p("0"), ctx.false_());
+        assert_eq!(p("!a & b | c"), (!a & b) | c);
+        assert_eq!(p("a & (b | c)"), a & (b | c));
+        assert_eq!(p("a | b ^ c"), a | (b ^ c));
+        assert_eq!(p("a -> b -> c"), !a | (!b | c));
+        assert_eq!(p("a <-> b"), !(a ^ b));
+        assert_eq!(p("a -> b <-> !a | b"), ctx.true_());
+        assert_eq!(p("ite(a, b, c)"), (a & b) | (!a & c));
+        assert_eq!(p("and(a, b, c)"), a & b & c);
+        assert_eq!(p("or(a)\n | !!b"), a | b);
+    });
+}
+
+#[test]
+fn test_parse_errors() {
+    super::use_bdd(|ctx| {
+        let var = |s: &str| if s == "x" { Some(0u8) } else { None };
+        let err = |s: &str| {
+            let e = ctx.parse(s, var).unwrap_err();
+            (e.line, e.column)
+        };
+        assert_eq!(err("x & y"), (1, 5));
+        assert_eq!(err("x &\n  (x | )"), (2, 8));
+        assert_eq!(err("x - x"), (1, 3));
+        assert_eq!(err("x x"), (1, 3));
+        assert_eq!(err("(x"), (1, 3));
+        assert_eq!(err("ite(x, x)"), (1, 1));
+        assert_eq!(err("x $"), (1, 3));
+        assert_eq!(ctx.parse("x &", var).unwrap_err().to_string(), "1:4: unexpected end of input");
+    });
+}
@@ -5,9 +5,46 @@
 // #![allow(dead_code)]
 
 use std::collections::hash_set::HashSet;
+use std::collections::hash_map::HashMap;
 use std::hash::Hash;
 use bumpalo::Bump;
 
+// Les fonctionnalités annexes de l'interface de bas niveau sont réparties dans
+// des sous-modules du module `raw`, qui ont accès à ses définitions privées.
+mod dot;
+mod print;
+mod binary;
+mod dddmp;
+mod buddy;
+mod dimacs;
+mod isop;
+mod pla;
+mod blif;
+mod aiger;
+mod codegen;
+mod netlist;
+mod truth;
+mod cardinality;
+mod linear;
+#[macro_use]
+mod unique;
+mod mtbdd;
+mod zdd;
+mod mdd;
+mod sensitivity;
+mod inspect;
+#[cfg(feature = "json")]
+mod json;
+
+pub use self::binary::{BinaryVar, BinaryInput};
+pub use self::dimacs::Cnf;
+pub use self::isop::Cover;
+pub use self::pla::PlaOutput;
+pub use self::mtbdd::{Float, Mtbdd, MtbddContext, Value};
+pub use self::zdd::{Zdd, ZddContext, ZddIter};
+pub use self::mdd::{Mdd, MddContext};
+pub use self::inspect::NodeView;
+
 // Un BDD est une structure de donnée comportant potentiellement beaucoup
 // d'alias. C'est ce qui permet un partage optimal et donc la compacité de la
 // structure de donnée.
@@ -52,15 +89,17 @@ use bumpalo::Bump;
 // Un nœud d'un BDD est soit terminal (True, False), soit interne.
 // Un nœud est `Copy` (donc `Clone`), et on utilise l'implémentation par défaut
 // pour les traits `Eq`, `PartialEq` et `Hash` pour pouvoir l'utiliser dans des
-// tables de hachage. On dérive aussi une implémentation de `Debug` pour avoir
-// des messages d'erreurs plus agréables lorsqu'un test échoue.
+// tables de hachage. On ne dérive pas `Debug` : l'implémentation dérivée
+// déplierait récursivement les fils sans tenir compte du partage, ce qui
+// produit un affichage de taille exponentielle. Les implémentations de `Debug`
+// et `Display` pour les BDDs sont définies dans le sous-module `print`.
 //
 // Comme toutes les structures de données de BDDs, ce type est paramétré par la
 // durée de vie 'arena, correspondant à la durée de vie des pointeurs utilisés
 // dans l'arène d'allocation.
 // De même, ce type est paramètré par le type `V` des variables booléennes du
 // BDD.
-#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
 enum Node<'arena, V> {
     False, True,
     If { var: V, children: [Bdd<'arena, V>; 2] }
@@ -70,22 +109,33 @@ enum Node<'arena, V> {
 // BDDs. Ce type a les invariants suivants:
 //   - L'emprunt pointe vers l'arène d'allocation des BDDs.
 //   - Aucun autre BDD dans l'arène lui est isomorphe.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone)]
 pub struct Bdd<'arena, V>(&'arena Node<'arena, V>);
 
 // On redéfinit l'égalité et la fonction de hachage du type des BDDs, afin
 // d'utiliser l'invariant d'unicité: puisque que chaque BDD ne peut être
 // représenté qu'une seule fois dans l'arène d'allocation, il suffit de comparer
 // et de hacher l'*adresse*, plutôt que de parcourir le BDD récrusivement.
+// Les terminaux font exception : ils ne sont pas alloués dans l'arène, et
+// rien ne garantit que deux occurrences de `&Node::True` dans le code aient la
+// même adresse (le compilateur peut dupliquer ces constantes, notamment entre
+// unités de compilation). On les compare donc par leur contenu.
 impl<'arena, V> PartialEq<Bdd<'arena, V>> for Bdd<'arena, V> {
     fn eq(&self, x: &Bdd<'arena, V>) -> bool {
-        std::ptr::eq(self.0, x.0)
+        match (self.0, x.0) {
+            (Node::True, Node::True) | (Node::False, Node::False) => true,
+            _ => std::ptr::eq(self.0, x.0)
+        }
     }
 }
 impl<'arena, V> Eq for Bdd<'arena, V> { }
 impl<'arena, V> Hash for Bdd<'arena, V> {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
-        std::ptr::hash(self.0, state)
+        match self.0 {
+            Node::False => state.write_u8(0),
+            Node::True => state.write_u8(1),
+            Node::If { .. } => std::ptr::hash(self.0, state)
+        }
     }
 }
 
@@ -101,8 +151,14 @@ pub struct Context<'arena, V> {
     // contient jamais deux BDDs isomorphes.
     hashcons: HashSet<&'arena Node<'arena, V>>,
 
-    // À COMPLÉTER AVEC D'AUTRES STRUCTURES DE DONNÉES GLOBALES (COMME LES
-    // TABLES DE MÉMOÏSATION).
+    // Les tables de mémoïsation de `not` et `and`. Elles sont conservées
+    // d'un appel à l'autre, ce qui évite de refaire les mêmes calculs sur des
+    // BDDs proches. Les clés de `and_memo` sont ordonnées par adresse, la
+    // conjonction étant commutative. Ces tables ne sont jamais vidées : leur
+    // taille croît comme celle de l'arène, pendant toute la durée de vie du
+    // contexte.
+    not_memo: HashMap<Bdd<'arena, V>, Bdd<'arena, V>>,
+    and_memo: HashMap<(Bdd<'arena, V>, Bdd<'arena, V>), Bdd<'arena, V>>,
 }
 
 
@@ -120,7 +176,8 @@ impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
     // `new` crée un nouveau contexte de BDD, à partir d'une arène d'allocation
     // déjà créée par l'appelant.
     pub fn new(alloc: &'arena Bump) -> Self {
-        return Context { alloc: alloc, hashcons: HashSet::new() }
+        return Context { alloc: alloc, hashcons: HashSet::new(),
+                         not_memo: HashMap::new(), and_memo: HashMap::new() }
     }
 
     // La méthode privée `hashcons` prend un nœud en paramètre, et
@@ -149,6 +206,49 @@ impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
         return self.hashcons(node)
     }
 
+    // La méthode privée `branch` construit, comme `node`, le BDD dont la
+    // racine teste `var` et a pour fils `children`, mais sans supposer que
+    // `var` est plus petite que les variables apparaissant dans `children`.
+    // Elle est utilisée lors du chargement de BDDs produits par d'autres
+    // outils, dont l'ordre des variables peut différer de celui de `V`.
+    fn branch(&mut self, var: V, children: [Bdd<'arena, V>; 2]) -> Bdd<'arena, V> {
+        let ordered = |b: Bdd<'arena, V>| match b {
+            Bdd(Node::If { var: v, .. }) => var < *v,
+            _ => true
+        };
+        if ordered(children[0]) && ordered(children[1]) {
+            return self.node(var, children)
+        }
+        let x = self.var(var);
+        let nx = self.not(x);
+        let lo = self.and(nx, children[0]);
+        let hi = self.and(x, children[1]);
+        return self.or(lo, hi)
+    }
+
+    // La méthode privée `literals` construit directement le BDD d'une
+    // conjonction (si `conj` est vrai) ou d'une disjonction de littéraux
+    // `(variable, polarité)`, en partant de la plus grande variable. Chaque
+    // nœud a pour fils l'élément absorbant de l'opération (FAUX pour une
+    // conjonction, VRAI pour une disjonction) et le BDD des littéraux
+    // suivants. Un littéral et son opposé donnent l'élément absorbant.
+    fn literals(&mut self, lits: &[(V, bool)], conj: bool) -> Bdd<'arena, V> {
+        let mut lits = lits.to_vec();
+        lits.sort();
+        lits.dedup();
+        let absorbing = if conj { self.false_() } else { self.true_() };
+        let mut res = if conj { self.true_() } else { self.false_() };
+        for (i, &(v, pos)) in lits.iter().enumerate().rev() {
+            if lits.get(i + 1).is_some_and(|&(w, _)| w == v) {
+                return absorbing
+            }
+            let mut children = [absorbing; 2];
+            children[(pos == conj) as usize] = res;
+            res = self.node(v, children)
+        }
+        return res
+    }
+
     // La méthode `true_` renvoie le BDD correspondant à la formule booléenne
     // VRAI.
     pub fn true_(&mut self) -> Bdd<'arena, V> {
@@ -179,8 +279,13 @@ impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
             Bdd(&Node::True) => Bdd(&Node::False),
             Bdd(&Node::False) => Bdd(&Node::True),
             Bdd(&Node::If {var, children}) => {
+                if let Some(&res) = self.not_memo.get(&x) {
+                    return res
+                }
                 let children2 = [self.not(children[0]), self.not(children[1])];
-                return self.node(var, children2)
+                let res = self.node(var, children2);
+                self.not_memo.insert(x, res);
+                return res
             }
         }
     }
@@ -196,16 +301,22 @@ impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
             (Bdd(&Node::False), _) => return Bdd(&Node::False),
             (_, Bdd(&Node::False)) => return Bdd(&Node::False),
             (Bdd(Node::If{var : var_a, children : children_a}), Bdd(Node::If{var : var_b, children : children_b})) => {
-                if var_a == var_b {
+                let key = if (a.0 as *const Node<V>) < (b.0 as *const Node<V>) { (a, b) } else { (b, a) };
+                if let Some(&res) = self.and_memo.get(&key) {
+                    return res
+                }
+                let res = if var_a == var_b {
                     let children2 = [self.and(children_a[0], children_b[0]), self.and(children_a[1], children_b[1])];
-                    return self.node(*var_a, children2)
+                    self.node(*var_a, children2)
                 } else if var_a < var_b {
                     let children2 = [self.and(children_a[0], b), self.and(children_a[1], b)];
-                    return self.node(*var_a, children2)
+                    self.node(*var_a, children2)
                 } else {
                     let children2 = [self.and(a, children_b[0]), self.and(a, children_b[1])];
-                    return self.node(*var_b, children2)
-                }
+                    self.node(*var_b, children2)
+                };
+                self.and_memo.insert(key, res);
+                return res
             }
         }
     }
@@ -225,6 +336,91 @@ impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
         let naab = self.not(aab);
         return self.and(aob, naab);
     }
+
+    // La méthode `ite` renvoie le BDD de la formule « si `c` alors `t` sinon
+    // `e` ».
+    pub fn ite(&mut self, c: Bdd<'arena, V>, t: Bdd<'arena, V>, e: Bdd<'arena, V>)
+               -> Bdd<'arena, V> {
+        let ct = self.and(c, t);
+        let nc = self.not(c);
+        let nce = self.and(nc, e);
+        return self.or(ct, nce)
+    }
+
+    // La méthode privée `quantify` élimine de `f` les variables de `vars`
+    // (triées dans l'ordre croissant), en combinant les deux cofacteurs de
+    // chacune par une disjonction (`forall` faux) ou une conjonction (`forall`
+    // vrai). Les résultats sont mémoïsés dans `memo`, propre à l'appel.
+    fn quantify(&mut self, f: Bdd<'arena, V>, vars: &[V], forall: bool,
+                memo: &mut HashMap<Bdd<'arena, V>, Bdd<'arena, V>>) -> Bdd<'arena, V> {
+        match f {
+            Bdd(Node::If { var, children }) => {
+                // Les variables de `f` sont toutes plus grandes que `var`.
+                if vars.last() < Some(var) {
+                    return f
+                }
+                if let Some(&res) = memo.get(&f) {
+                    return res
+                }
+                let lo = self.quantify(children[0], vars, forall, memo);
+                let hi = self.quantify(children[1], vars, forall, memo);
+                let res = if vars.binary_search(var).is_err() {
+                    self.node(*var, [lo, hi])
+                } else if forall {
+                    self.and(lo, hi)
+                } else {
+                    self.or(lo, hi)
+                };
+                memo.insert(f, res);
+                return res
+            }
+            _ => return f
+        }
+    }
+
+    // Les méthodes `exists` et `forall` renvoient les quantifications
+    // existentielle et universelle de `f` sur les variables de `vars`.
+    pub fn exists(&mut self, f: Bdd<'arena, V>, vars: &[V]) -> Bdd<'arena, V> {
+        let mut vars = vars.to_vec();
+        vars.sort();
+        vars.dedup();
+        return self.quantify(f, &vars, false, &mut HashMap::new())
+    }
+
+    pub fn forall(&mut self, f: Bdd<'arena, V>, vars: &[V]) -> Bdd<'arena, V> {
+        let mut vars = vars.to_vec();
+        vars.sort();
+        vars.dedup();
+        return self.quantify(f, &vars, true, &mut HashMap::new())
+    }
+
+    // La méthode privée `rename_rec` renomme les variables de `f` selon
+    // `map`, en mémoïsant les résultats dans `memo`, propre à l'appel.
+    fn rename_rec<F: Fn(V) -> V>(&mut self, f: Bdd<'arena, V>, map: &F,
+                                 memo: &mut HashMap<Bdd<'arena, V>, Bdd<'arena, V>>) -> Bdd<'arena, V> {
+        match f {
+            Bdd(Node::If { var, children }) => {
+                if let Some(&res) = memo.get(&f) {
+                    return res
+                }
+                let lo = self.rename_rec(children[0], map, memo);
+                let hi = self.rename_rec(children[1], map, memo);
+                // Le renommage ne préserve pas forcément l'ordre des
+                // variables : `branch` rétablit l'ordre si besoin.
+                let res = self.branch(map(*var), [lo, hi]);
+                memo.insert(f, res);
+                return res
+            }
+            _ => return f
+        }
+    }
+
+    // La méthode `rename` renvoie le BDD obtenu en remplaçant chaque variable
+    // `v` de `f` par `map(v)`. La fonction `map` doit être injective sur les
+    // variables de `f`.
+    pub fn rename<F: Fn(V) -> V>(&mut self, f: Bdd<'arena, V>, map: F) -> Bdd<'arena, V> {
+        return self.rename_rec(f, &map, &mut HashMap::new())
+    }
 }
 
 impl<'arena, V: Hash + Copy + Ord> Bdd<'arena, V> {
@@ -257,6 +453,47 @@ impl<'arena, V: Hash + Copy + Ord> Bdd<'arena, V> {
     }
 }
 
+// La fonction `topological` renvoie la liste des nœuds internes accessibles
+// depuis les BDDs de `roots`, sans doublons, et de telle sorte que chaque nœud
+// apparaisse après ses fils. Les nœuds terminaux ne sont pas inclus dans la
+// liste : ils sont traités à part par les fonctions qui l'utilisent.
+pub(crate) fn topological<'arena, V: Copy>(roots: &[Bdd<'arena, V>]) -> Vec<Bdd<'arena, V>> {
+    fn visit<'arena, V: Copy>(b: Bdd<'arena, V>, seen: &mut HashSet<Bdd<'arena, V>>,
+                        out: &mut Vec<Bdd<'arena, V>>) {
+        if let Bdd(Node::If { children, .. }) = b {
+            if seen.insert(b) {
+                visit(children[0], seen, out);
+                visit(children[1], seen, out);
+                out.push(b)
+            }
+        }
+    }
+    let mut seen = HashSet::new();
+    let mut out = Vec::new();
+    for &r in roots {
+        visit(r, &mut seen, &mut out)
+    }
+    return out
+}
+
+// La fonction `support` renvoie la liste triée et sans doublons des variables
+// apparaissant dans les nœuds de `nodes`.
+pub(crate) fn support<'arena, V: Copy + Ord>(nodes: &[Bdd<'arena, V>]) -> Vec<V> {
+    let mut vars: Vec<V> = nodes.iter().filter_map(|b| match b {
+        Bdd(Node::If { var, .. }) => Some(*var),
+        _ => None
+    }).collect();
+    vars.sort();
+    vars.dedup();
+    return vars
+}
+
+// La fonction `invalid` construit l'erreur renvoyée par les fonctions de
+// chargement lorsque le fichier lu est mal formé.
+pub(crate) fn invalid(msg: &str) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, msg)
+}
+
 #[test]
 fn test_new_ctx() {
     let allo = bumpalo::Bump::new();
@@ -479,3 +716,64 @@ fn test_nsat() {
     assert_eq!(t.nsat(&[]), 1);
     assert_eq!(f.nsat(&[]), 0);
 }
+
+#[test]
+fn test_quantifiers() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let x = ctx.var(0);
+    let y = ctx.var(1);
+    let z = ctx.var(2);
+    let t = ctx.true_();
+    let f = ctx.false_();
+
+    let xy = ctx.and(x, y);
+    let f1 = ctx.or(xy, z);
+    assert_eq!(ctx.exists(f1, &[0]), ctx.or(y, z));
+    assert_eq!(ctx.forall(f1, &[0]), z);
+    assert_eq!(ctx.exists(f1, &[0, 1]), t);
+    assert_eq!(ctx.forall(f1, &[2, 0]), f);
+    assert_eq!(ctx.exists(f1, &[3]), f1);
+    let nx = ctx.not(x);
+    let nxz = ctx.and(nx, z);
+    assert_eq!(ctx.ite(x, y, z), ctx.or(xy, nxz));
+}
+
+#[test]
+fn test_terminal_identity() {
+    // Un terminal qui n'est pas la constante `&Node::True` (ou `&Node::False`)
+    // utilisée par le contexte, par exemple une copie de la constante dans une
+    // autre unité de compilation, doit lui rester égal, avec le même haché.
+    let allo = bumpalo::Bump::new();
+    let mut ctx: Context<u8> = Context::new(&allo);
+    let t = Bdd(&*allo.alloc(Node::True));
+    let f = Bdd(&*allo.alloc(Node::False));
+    assert!(!std::ptr::eq(t.0, ctx.true_().0));
+    assert!(t == ctx.true_() && f == ctx.false_() && t != f);
+    let set: HashSet<Bdd<u8>> = [t, ctx.true_(), f, ctx.false_()].iter().copied().collect();
+    assert_eq!(set.len(), 2);
+    let x = ctx.var(0);
+    let nx = ctx.not(x);
+    assert_eq!(ctx.or(x, nx), t);
+    assert_eq!(ctx.and(x, nx), f);
+}
+
+#[test]
+fn test_rename() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let x = ctx.var(0);
+    let y = ctx.var(1);
+    let z = ctx.var(2);
+    let ny = ctx.not(y);
+    let f = ctx.and(x, ny);
+    // Renommage préservant l'ordre, puis l'inversant.
+    let nz = ctx.not(z);
+    assert_eq!(ctx.rename(f, |v| v + 1), ctx.and(y, nz));
+    let nx = ctx.not(x);
+    assert_eq!(ctx.rename(f, |v| 1 - v), ctx.and(y, nx));
+    let g = ctx.xor(f, z);
+    let h = ctx.rename(g, |v| 2 - v);
+    let nyz = ctx.and(z, ny);
+    assert_eq!(h, ctx.xor(nyz, x));
+}
@@ -0,0 +1,231 @@
+// Import de circuits combinatoires au format AIGER (graphes ET-inverseur),
+// dans ses variantes ASCII (`aag`) et binaire (`aig`).
+//
+// Un fichier AIGER commence par l'en-tête `aag M I L O A` (ou `aig ...`), où M
+// est le plus grand indice de variable, et I, L, O et A sont les nombres
+// d'entrées, de bascules, de sorties et de portes ET. Les signaux sont
+// désignés par des littéraux : le littéral `2v` est la variable `v`, `2v + 1`
+// sa négation, et les littéraux 0 et 1 sont les constantes FAUX et VRAI.
+//
+// Dans la variante ASCII, l'en-tête est suivi d'une ligne par entrée (son
+// littéral), par sortie (son littéral) et par porte ET (`lhs rhs0 rhs1`). Dans
+// la variante binaire, les entrées sont implicitement les littéraux 2 à 2I, et
+// les portes ET, numérotées dans l'ordre, sont codées par les différences
+// `lhs - rhs0` et `rhs0 - rhs1`, écrites en nombres de taille variable (7 bits
+// par octet, le bit de poids fort indiquant que le nombre continue). Dans les
+// deux cas, une table des symboles facultative (lignes `i<k> nom` et
+// `o<k> nom`) nomme les entrées et les sorties, et une ligne `c` commence les
+// commentaires.
+//
+// Seuls les circuits combinatoires sont gérés : un circuit avec des bascules
+// (ou les extensions de la version 1.9 du format) produit une erreur.
+
+use std::hash::Hash;
+use std::io::{self, Read};
+use super::{Bdd, Context, invalid};
+use super::dddmp::parse;
+
+// La définition d'une variable AIGER.
+#[derive(Clone, Copy)]
+enum Def {
+    Input(usize),
+    And(usize, usize),
+}
+
+// Un curseur sur le contenu d'un fichier AIGER, qui mélange lignes de texte et
+// données binaires.
+struct Input<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Input<'a> {
+    fn line(&mut self) -> io::Result<&'a str> {
+        let rest = &self.data[self.pos..];
+        if rest.is_empty() {
+            return Err(invalid("unexpected end of file"))
+        }
+        let len = rest.iter().position(|&c| c == b'\n').unwrap_or(rest.len());
+        self.pos += (len + 1).min(rest.len());
+        return std::str::from_utf8(&rest[..len]).map_err(|_| invalid("invalid UTF-8"))
+    }
+
+    fn literal(&mut self) -> io::Result<usize> {
+        return parse(self.line()?.trim())
+    }
+
+    fn varint(&mut self) -> io::Result<usize> {
+        let mut res: usize = 0;
+        let mut shift = 0;
+        loop {
+            let byte = *self.data.get(self.pos).ok_or_else(|| invalid("unexpected end of file"))?;
+            self.pos += 1;
+            if shift >= usize::BITS {
+                return Err(invalid("number too large"))
+            }
+            res |= ((byte & 0x7f) as usize) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(res)
+            }
+            shift += 7
+        }
+    }
+}
+
+impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
+    // La méthode `read_aiger` lit un circuit combinatoire au format AIGER
+    // (ASCII ou binaire), et renvoie le BDD de chacune de ses sorties, avec son
+    // nom (vide si la table des symboles ne le donne pas), en fonction des
+    // entrées. `var` associe à chaque entrée, numérotée à partir de 0, la
+    // variable correspondante : elle détermine donc l'ordre des entrées dans
+    // les BDDs.
+    pub fn read_aiger<R: Read, F>(&mut self, r: &mut R, var: F)
+                                  -> io::Result<Vec<(String, Bdd<'arena, V>)>>
+      where F: Fn(usize) -> V {
+        let mut data = Vec::new();
+        r.read_to_end(&mut data)?;
+        let mut input = Input { data: &data, pos: 0 };
+
+        let header: Vec<&str> = input.line()?.split_whitespace().collect();
+        let binary = match header.first() {
+            Some(&"aag") => false,
+            Some(&"aig") => true,
+            _ => return Err(invalid("not an AIGER file"))
+        };
+        if header.len() < 6 {
+            return Err(invalid("malformed header"))
+        }
+        let counts: Vec<usize> = header[1..].iter().map(|s| parse(s)).collect::<io::Result<_>>()?;
+        let (m, ni, nl, no, na) = (counts[0], counts[1], counts[2], counts[3], counts[4]);
+        if nl != 0 || counts[5..].iter().any(|&c| c != 0) {
+            return Err(invalid("only combinational AIGER circuits are supported"))
+        }
+        if ni + na > m {
+            return Err(invalid("malformed header"))
+        }
+
+        let mut defs: Vec<Option<Def>> = vec![None; m + 1];
+        let mut define = |lit: usize, def: Def| {
+            if lit % 2 == 1 || lit == 0 || lit / 2 > m || defs[lit / 2].is_some() {
+                return Err(invalid("invalid definition"))
+            }
+            defs[lit / 2] = Some(def);
+            return Ok(())
+        };
+        for i in 0..ni {
+            let lit = if binary { 2 * (i + 1) } else { input.literal()? };
+            define(lit, Def::Input(i))?
+        }
+        let outputs: Vec<usize> = (0..no).map(|_| input.literal()).collect::<io::Result<_>>()?;
+        for i in 0..na {
+            let (lhs, rhs0, rhs1) = if binary {
+                let lhs = 2 * (ni + i + 1);
+                let rhs0 = lhs.checked_sub(input.varint()?);
+                let rhs1 = rhs0.and_then(|r| input.varint().ok().and_then(|d| r.checked_sub(d)));
+                match (rhs0, rhs1) {
+                    (Some(rhs0), Some(rhs1)) => (lhs, rhs0, rhs1),
+                    _ => return Err(invalid("malformed AND gate"))
+                }
+            } else {
+                let lits: Vec<usize> = input.line()?.split_whitespace()
+                    .map(parse).collect::<io::Result<_>>()?;
+                match lits[..] {
+                    [lhs, rhs0, rhs1] => (lhs, rhs0, rhs1),
+                    _ => return Err(invalid("malformed AND gate"))
+                }
+            };
+            define(lhs, Def::And(rhs0, rhs1))?
+        }
+
+        // Table des symboles.
+        let mut names = vec![String::new(); no];
+        while input.pos < data.len() {
+            let line = input.line()?;
+            if line.starts_with('c') {
+                break
+            }
+            if let Some(rest) = line.strip_prefix('o') {
+                let (k, name) = rest.split_once(' ').ok_or_else(|| invalid("malformed symbol"))?;
+                let k: usize = parse(k)?;
+                *names.get_mut(k).ok_or_else(|| invalid("malformed symbol"))? = name.to_string()
+            }
+        }
+
+        // Évaluation des variables dans l'ordre topologique, avec une pile
+        // explicite, car les portes d'un fichier ASCII peuvent être données
+        // dans n'importe quel ordre. Une variable est empilée une première fois
+        // (`false`) pour empiler ses opérandes, puis une seconde fois (`true`)
+        // pour être évaluée.
+        let mut vals: Vec<Option<Bdd<'arena, V>>> = vec![None; m + 1];
+        vals[0] = Some(self.false_());
+        let mut visiting = vec![false; m + 1];
+        let mut res = Vec::with_capacity(no);
+        for (&out, name) in outputs.iter().zip(names) {
+            if out / 2 > m {
+                return Err(invalid("invalid literal"))
+            }
+            let mut stack = vec![(out / 2, false)];
+            while let Some((v, ready)) = stack.pop() {
+                if vals[v].is_some() {
+                    continue
+                }
+                let def = defs[v].ok_or_else(|| invalid("undefined literal"))?;
+                match def {
+                    Def::Input(i) => vals[v] = Some(self.var(var(i))),
+                    Def::And(a, b) if ready => {
+                        let lit = |ctx: &mut Self, l: usize| {
+                            let x = vals[l / 2].unwrap();
+                            if l % 2 == 1 { ctx.not(x) } else { x }
+                        };
+                        let (x, y) = (lit(self, a), lit(self, b));
+                        vals[v] = Some(self.and(x, y))
+                    }
+                    Def::And(a, b) => {
+                        if a / 2 > m || b / 2 > m {
+                            return Err(invalid("invalid literal"))
+                        }
+                        if visiting[v] {
+                            return Err(invalid("combinational cycle"))
+                        }
+                        visiting[v] = true;
+                        stack.extend([(v, true), (a / 2, false), (b / 2, false)])
+                    }
+                }
+            }
+            let x = vals[out / 2].unwrap();
+            res.push((name, if out % 2 == 1 { self.not(x) } else { x }))
+        }
+        return Ok(res)
+    }
+}
+
+#[test]
+fn test_read_aiger() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let a = ctx.var(1u8);
+    let b = ctx.var(0);
+    let ab = ctx.and(a, b);
+    let nand = ctx.not(ab);
+    let xor = ctx.xor(a, b);
+
+    // Les portes sont données dans le désordre.
+    let text = "aag 5 2 0 3 3\n2\n4\n7\n10\n1\n10 7 9\n6 2 4\n8 3 5\ni0 a\no0 nand\no1 xor\nc\ncommentaire\n";
+    let outs = ctx.read_aiger(&mut text.as_bytes(), |i| 1 - i as u8).unwrap();
+    assert_eq!(outs, vec![("nand".to_string(), nand), ("xor".to_string(), xor),
+                          (String::new(), ctx.true_())]);
+
+    // Le même circuit au format binaire, avec les portes renumérotées.
+    let mut data = b"aig 5 2 0 3 3\n7\n10\n1\n".to_vec();
+    data.extend([2, 2, 3, 2, 1, 2]);
+    data.extend(b"o0 nand\no1 xor\n");
+    let outs = ctx.read_aiger(&mut &data[..], |i| 1 - i as u8).unwrap();
+    assert_eq!(outs, vec![("nand".to_string(), nand), ("xor".to_string(), xor),
+                          (String::new(), ctx.true_())]);
+
+    let latch = "aag 2 1 1 1 0\n2\n4 2\n4\n";
+    assert!(ctx.read_aiger(&mut latch.as_bytes(), |i| i as u8).is_err());
+    let cycle = "aag 2 0 0 1 2\n2\n2 4 1\n4 2 1\n";
+    assert!(ctx.read_aiger(&mut cycle.as_bytes(), |i| i as u8).is_err());
+    assert!(ctx.read_aiger(&mut "aag 1 0 0 1 0\n2\n".as_bytes(), |i| i as u8).is_err());
+}
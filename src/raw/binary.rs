@@ -0,0 +1,298 @@
+// Format binaire natif pour la sauvegarde d'un ensemble de BDDs.
+//
+// Un fichier contient un ensemble de racines nommées, ainsi que tous les nœuds
+// qu'elles partagent, chacun n'étant écrit qu'une seule fois. Tous les entiers
+// sont écrits en petit-boutiste. Le format est le suivant :
+//   - l'en-tête : les 4 octets `BDDF`, la version du format (1 octet), puis le
+//     code du type des variables (1 octet, voir le trait `BinaryVar`) ;
+//   - l'ordre des variables : leur nombre (u32), puis les variables utilisées,
+//     dans l'ordre croissant ;
+//   - les nœuds : leur nombre (u32), puis, pour chaque nœud, l'indice de sa
+//     variable dans l'ordre (u32) et les identifiants de ses fils
+//     `children[0]` et `children[1]` (u32). Les identifiants 0 et 1 désignent
+//     les terminaux FAUX et VRAI, et le i-ème nœud du fichier a l'identifiant
+//     i + 2. Les nœuds sont écrits dans l'ordre topologique : les fils d'un
+//     nœud sont toujours écrits avant lui ;
+//   - les racines : leur nombre (u32), puis, pour chaque racine, la longueur
+//     de son nom (u32), son nom en UTF-8 et son identifiant (u32) ;
+//   - une somme de contrôle FNV-1a sur 64 bits de tout ce qui précède.
+//
+// Lors du chargement, on vérifie que le BDD lu est bien canonique (réduit et
+// ordonné, sans nœuds dupliqués), afin de ne pas casser les invariants du
+// contexte.
+
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+use super::{Bdd, Context, Node, topological, support, invalid};
+
+const MAGIC: &[u8; 4] = b"BDDF";
+const VERSION: u8 = 1;
+
+// Le trait `BinaryVar` décrit l'encodage binaire d'un type de variables.
+// `TAG` identifie le type dans l'en-tête du fichier, ce qui permet de refuser
+// un fichier écrit avec un autre type de variables.
+pub trait BinaryVar: Sized {
+    const TAG: u8;
+    fn write_to(self, out: &mut Vec<u8>);
+    fn read_from(input: &mut BinaryInput) -> io::Result<Self>;
+}
+
+macro_rules! binary_var {
+    ($t: ty, $tag: expr) => {
+        impl BinaryVar for $t {
+            const TAG: u8 = $tag;
+            fn write_to(self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes())
+            }
+            fn read_from(input: &mut BinaryInput) -> io::Result<Self> {
+                let mut bytes = [0; std::mem::size_of::<$t>()];
+                let n = bytes.len();
+                bytes.copy_from_slice(input.take(n)?);
+                return Ok(<$t>::from_le_bytes(bytes))
+            }
+        }
+    }
+}
+
+binary_var!(u8, 1);
+binary_var!(u16, 2);
+binary_var!(u32, 3);
+binary_var!(u64, 4);
+
+// Le type `BinaryInput` est un curseur sur les octets d'un fichier en cours de
+// lecture.
+pub struct BinaryInput<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BinaryInput<'a> {
+    // La méthode `take` renvoie les `n` octets suivants, ou une erreur si le
+    // fichier est tronqué.
+    pub fn take(&mut self, n: usize) -> io::Result<&'a [u8]> {
+        if self.bytes.len() - self.pos < n {
+            return Err(invalid("unexpected end of file"))
+        }
+        let res = &self.bytes[self.pos..self.pos + n];
+        self.pos += n;
+        return Ok(res)
+    }
+
+    fn u32(&mut self) -> io::Result<u32> {
+        return u32::read_from(self)
+    }
+}
+
+// La fonction `fnv1a` calcule la somme de contrôle du fichier.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut h: u64 = 0xcbf29ce484222325;
+    for &b in bytes {
+        h ^= b as u64;
+        h = h.wrapping_mul(0x100000001b3);
+    }
+    return h
+}
+
+fn write_u32(out: &mut Vec<u8>, x: usize) -> io::Result<()> {
+    if x > u32::MAX as usize {
+        return Err(io::Error::other("too many nodes for the binary format"))
+    }
+    (x as u32).write_to(out);
+    return Ok(())
+}
+
+impl<'arena, V: Hash + Copy + Ord + BinaryVar> Context<'arena, V> {
+    // La méthode `save` écrit dans `w` les BDDs de `roots`, avec leurs noms.
+    pub fn save<W: Write>(&self, w: &mut W, roots: &[(&str, Bdd<'arena, V>)]) -> io::Result<()> {
+        let bdds: Vec<Bdd<'arena, V>> = roots.iter().map(|&(_, b)| b).collect();
+        let nodes = topological(&bdds);
+
+        let vars = support(&nodes);
+        let var_ids: HashMap<V, usize> =
+            vars.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+
+        let mut ids: HashMap<Bdd<'arena, V>, usize> = HashMap::new();
+        let id = |ids: &HashMap<Bdd<'arena, V>, usize>, b: Bdd<'arena, V>| match b {
+            Bdd(Node::False) => 0,
+            Bdd(Node::True) => 1,
+            Bdd(Node::If { .. }) => ids[&b],
+        };
+
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.push(V::TAG);
+        write_u32(&mut out, vars.len())?;
+        for &v in &vars {
+            v.write_to(&mut out)
+        }
+        write_u32(&mut out, nodes.len())?;
+        for (i, &b) in nodes.iter().enumerate() {
+            if let Bdd(Node::If { var, children }) = b {
+                write_u32(&mut out, var_ids[var])?;
+                write_u32(&mut out, id(&ids, children[0]))?;
+                write_u32(&mut out, id(&ids, children[1]))?;
+            }
+            ids.insert(b, i + 2);
+        }
+        write_u32(&mut out, roots.len())?;
+        for &(name, b) in roots {
+            write_u32(&mut out, name.len())?;
+            out.extend_from_slice(name.as_bytes());
+            write_u32(&mut out, id(&ids, b))?;
+        }
+        let checksum = fnv1a(&out);
+        checksum.write_to(&mut out);
+        return w.write_all(&out)
+    }
+
+    // La méthode `load` lit un ensemble de BDDs écrit par `save`, et les
+    // reconstruit dans ce contexte. Elle renvoie les racines avec leurs noms,
+    // dans l'ordre dans lequel elles ont été sauvegardées.
+    pub fn load<R: Read>(&mut self, r: &mut R) -> io::Result<Vec<(String, Bdd<'arena, V>)>> {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)?;
+        if bytes.len() < 8 {
+            return Err(invalid("unexpected end of file"))
+        }
+        let (body, checksum) = bytes.split_at(bytes.len() - 8);
+        if u64::from_le_bytes(checksum.try_into().unwrap()) != fnv1a(body) {
+            return Err(invalid("checksum mismatch"))
+        }
+        let mut input = BinaryInput { bytes: body, pos: 0 };
+
+        if input.take(4)? != MAGIC {
+            return Err(invalid("not a BDD file"))
+        }
+        if input.take(1)?[0] != VERSION {
+            return Err(invalid("unsupported format version"))
+        }
+        if input.take(1)?[0] != V::TAG {
+            return Err(invalid("variable type mismatch"))
+        }
+
+        let nvars = input.u32()?;
+        let mut vars = Vec::new();
+        for _ in 0..nvars {
+            let v = V::read_from(&mut input)?;
+            if vars.last().is_some_and(|&last| last >= v) {
+                return Err(invalid("variable order is not increasing"))
+            }
+            vars.push(v)
+        }
+
+        let nnodes = input.u32()?;
+        let mut bdds = vec![self.false_(), self.true_()];
+        let mut seen = HashSet::new();
+        for _ in 0..nnodes {
+            let var = input.u32()? as usize;
+            let lo = input.u32()? as usize;
+            let hi = input.u32()? as usize;
+            if var >= vars.len() || lo >= bdds.len() || hi >= bdds.len() {
+                return Err(invalid("dangling reference"))
+            }
+            if lo == hi {
+                return Err(invalid("BDD is not reduced"))
+            }
+            if !seen.insert((var, lo, hi)) {
+                return Err(invalid("duplicate node"))
+            }
+            let var = vars[var];
+            let children = [bdds[lo], bdds[hi]];
+            for c in &children {
+                if let Bdd(Node::If { var: cvar, .. }) = c {
+                    if *cvar <= var {
+                        return Err(invalid("BDD is not ordered"))
+                    }
+                }
+            }
+            bdds.push(self.node(var, children))
+        }
+
+        let nroots = input.u32()?;
+        let mut roots = Vec::new();
+        for _ in 0..nroots {
+            let len = input.u32()? as usize;
+            let name = std::str::from_utf8(input.take(len)?)
+                .map_err(|_| invalid("root name is not valid UTF-8"))?;
+            let id = input.u32()? as usize;
+            if id >= bdds.len() {
+                return Err(invalid("dangling reference"))
+            }
+            roots.push((name.to_string(), bdds[id]))
+        }
+        if input.pos != body.len() {
+            return Err(invalid("trailing data"))
+        }
+        return Ok(roots)
+    }
+}
+
+#[test]
+fn test_save_load() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx: Context<u16> = Context::new(&allo);
+    let x = ctx.var(0);
+    let y = ctx.var(3);
+    let z = ctx.var(7);
+    let xy = ctx.and(x, y);
+    let f = ctx.xor(xy, z);
+    let t = ctx.true_();
+
+    let mut buf = Vec::new();
+    ctx.save(&mut buf, &[("f", f), ("xy", xy), ("t", t)]).unwrap();
+
+    // Chargement dans le même contexte : on retrouve les mêmes nœuds.
+    let roots = ctx.load(&mut &buf[..]).unwrap();
+    assert_eq!(roots, vec![("f".to_string(), f), ("xy".to_string(), xy), ("t".to_string(), t)]);
+
+    // Chargement dans un autre contexte.
+    let allo2 = bumpalo::Bump::new();
+    let mut ctx2: Context<u16> = Context::new(&allo2);
+    let roots = ctx2.load(&mut &buf[..]).unwrap();
+    assert_eq!(roots[0].1.nsat(&[0, 3, 7]), 4);
+    assert_eq!(roots[1].1.nsat(&[0, 3, 7]), 2);
+    assert_eq!(roots[2].1.nsat(&[0, 3, 7]), 8);
+
+    // Un fichier corrompu ou d'un autre type est refusé.
+    let mut bad = buf.clone();
+    bad[12] ^= 1;
+    assert!(ctx.load(&mut &bad[..]).is_err());
+    assert!(ctx.load(&mut &buf[..buf.len() - 1]).is_err());
+    let allo3 = bumpalo::Bump::new();
+    let mut ctx3: Context<u32> = Context::new(&allo3);
+    assert!(ctx3.load(&mut &buf[..]).is_err());
+}
+
+#[test]
+fn test_load_non_canonical() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx: Context<u8> = Context::new(&allo);
+
+    // Construit un fichier à la main, contenant un unique nœud `if x0 then
+    // VRAI else VRAI`, qui n'est pas réduit.
+    let file = |lo: u32, hi: u32| {
+        let mut out = Vec::new();
+        out.extend_from_slice(MAGIC);
+        out.push(VERSION);
+        out.push(u8::TAG);
+        1u32.write_to(&mut out);
+        0u8.write_to(&mut out);
+        1u32.write_to(&mut out);
+        0u32.write_to(&mut out);
+        lo.write_to(&mut out);
+        hi.write_to(&mut out);
+        1u32.write_to(&mut out);
+        0u32.write_to(&mut out);
+        2u32.write_to(&mut out);
+        let checksum = fnv1a(&out);
+        checksum.write_to(&mut out);
+        out
+    };
+    assert!(ctx.load(&mut &file(1, 1)[..]).is_err());
+    assert!(ctx.load(&mut &file(0, 2)[..]).is_err());
+    let x = ctx.var(0);
+    assert_eq!(ctx.load(&mut &file(0, 1)[..]).unwrap(), vec![(String::new(), x)]);
+}
@@ -0,0 +1,200 @@
+// Import de circuits combinatoires au format BLIF (Berkeley Logic Interchange
+// Format).
+//
+// Un modèle BLIF déclare ses entrées primaires (`.inputs`) et ses sorties
+// (`.outputs`), puis définit chaque signal interne ou de sortie par une
+// directive `.names e1 ... en s`, suivie d'une couverture en cubes donnant la
+// valeur de `s` en fonction des signaux `e1` à `en` : chaque ligne est formée
+// d'un motif de `n` caractères (`0`, `1` ou `-`) et de la valeur de sortie. Si
+// cette valeur est `1`, la fonction est la disjonction des cubes ; si elle est
+// `0`, c'est la négation de cette disjonction. Une directive `.names s` sans
+// entrées définit une constante. Les lignes peuvent être prolongées par `\`,
+// et `#` introduit un commentaire.
+//
+// Seuls les circuits combinatoires sont gérés : les directives `.latch`,
+// `.subckt` et `.gate` produisent une erreur. Les signaux peuvent être définis
+// dans n'importe quel ordre ; ils sont évalués dans l'ordre topologique, avec
+// les opérations `and`, `or` et `not` du contexte (et donc ses tables de
+// mémoïsation).
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::{self, Read};
+use super::{Bdd, Context, invalid};
+
+// La définition d'un signal par une directive `.names`.
+struct Names {
+    inputs: Vec<String>,
+    cubes: Vec<(Vec<u8>, u8)>,
+}
+
+impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
+    // La méthode privée `eval_names` calcule le BDD d'un signal défini par
+    // `.names`, à partir des BDDs de ses entrées.
+    fn eval_names(&mut self, def: &Names, inputs: &[Bdd<'arena, V>]) -> io::Result<Bdd<'arena, V>> {
+        let mut res = self.false_();
+        let mut polarity = None;
+        for (pattern, out) in &def.cubes {
+            if pattern.len() != inputs.len() || polarity.is_some_and(|p| p != *out) {
+                return Err(invalid("malformed cover"))
+            }
+            polarity = Some(*out);
+            let mut cube = self.true_();
+            for (&c, &b) in pattern.iter().zip(inputs) {
+                cube = match c {
+                    b'1' => self.and(cube, b),
+                    b'0' => { let nb = self.not(b); self.and(cube, nb) }
+                    b'-' => cube,
+                    _ => return Err(invalid("malformed cover"))
+                }
+            }
+            res = self.or(res, cube)
+        }
+        return match polarity {
+            Some(b'0') => Ok(self.not(res)),
+            None | Some(b'1') => Ok(res),
+            _ => Err(invalid("malformed cover"))
+        }
+    }
+
+    // La méthode `read_blif` lit un circuit combinatoire au format BLIF, et
+    // renvoie le BDD de chacune de ses sorties, avec son nom, en fonction des
+    // entrées primaires. `var` associe à chaque entrée, numérotée à partir de 0
+    // dans l'ordre de la directive `.inputs`, la variable correspondante : elle
+    // détermine donc l'ordre des entrées dans les BDDs.
+    pub fn read_blif<R: Read, F>(&mut self, r: &mut R, var: F)
+                                 -> io::Result<Vec<(String, Bdd<'arena, V>)>>
+      where F: Fn(usize) -> V {
+        let mut text = String::new();
+        r.read_to_string(&mut text)?;
+        let text = text.replace("\\\r\n", " ").replace("\\\n", " ");
+
+        let mut signals: HashMap<String, Bdd<'arena, V>> = HashMap::new();
+        let mut outputs: Vec<String> = Vec::new();
+        let mut defs: HashMap<String, Names> = HashMap::new();
+        let mut current: Option<String> = None;
+        let mut ninputs = 0;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap();
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            if tokens.is_empty() {
+                continue
+            }
+            match tokens[0] {
+                ".model" => (),
+                ".inputs" => for &name in &tokens[1..] {
+                    let x = self.var(var(ninputs));
+                    ninputs += 1;
+                    signals.insert(name.to_string(), x);
+                },
+                ".outputs" => outputs.extend(tokens[1..].iter().map(|s| s.to_string())),
+                ".names" => {
+                    let (out, inputs) = tokens[1..].split_last()
+                        .ok_or_else(|| invalid("malformed .names directive"))?;
+                    let def = Names { inputs: inputs.iter().map(|s| s.to_string()).collect(),
+                                      cubes: Vec::new() };
+                    if defs.insert(out.to_string(), def).is_some() {
+                        return Err(invalid("signal defined twice"))
+                    }
+                    current = Some(out.to_string())
+                }
+                ".end" => break,
+                ".latch" | ".subckt" | ".gate" | ".mlatch" =>
+                    return Err(invalid("only combinational BLIF models are supported")),
+                t if t.starts_with('.') => current = None,
+                _ => {
+                    let def = current.as_ref().and_then(|name| defs.get_mut(name))
+                        .ok_or_else(|| invalid("cube outside of a .names directive"))?;
+                    let cube = match tokens[..] {
+                        [out] => (Vec::new(), out.as_bytes()[0]),
+                        [pattern, out] => (pattern.as_bytes().to_vec(), out.as_bytes()[0]),
+                        _ => return Err(invalid("malformed cube"))
+                    };
+                    def.cubes.push(cube)
+                }
+            }
+        }
+
+        // Évaluation des signaux dans l'ordre topologique, avec une pile
+        // explicite pour ne pas dépendre de la profondeur du circuit. Un signal
+        // est empilé une première fois (`false`) pour empiler ses entrées, puis
+        // une seconde fois (`true`) pour être évalué.
+        let mut on_stack: HashMap<String, bool> = HashMap::new();
+        for out in &outputs {
+            let mut stack = vec![(out.clone(), false)];
+            while let Some((name, ready)) = stack.pop() {
+                if signals.contains_key(&name) {
+                    continue
+                }
+                let def = defs.get(&name).ok_or_else(|| invalid("undefined signal"))?;
+                if ready {
+                    let inputs: Vec<Bdd<'arena, V>> =
+                        def.inputs.iter().map(|i| signals[i]).collect();
+                    let b = self.eval_names(def, &inputs)?;
+                    signals.insert(name, b);
+                    continue
+                }
+                if on_stack.insert(name.clone(), true).is_some() {
+                    return Err(invalid("combinational cycle"))
+                }
+                stack.push((name, true));
+                for i in &def.inputs {
+                    if !signals.contains_key(i) {
+                        stack.push((i.clone(), false))
+                    }
+                }
+            }
+        }
+        return Ok(outputs.into_iter().map(|o| { let b = signals[&o]; (o, b) }).collect())
+    }
+}
+
+#[test]
+fn test_blif() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let text = "\
+.model adder   # additionneur 1 bit
+.inputs a b \\
+  cin
+.outputs s cout one
+.names t cin s
+10 1
+01 1
+.names a b t
+01 1
+10 1
+.names a b cin cout
+11- 1
+1-1 1
+-11 1
+.names one
+1
+.end
+";
+    let outs = ctx.read_blif(&mut text.as_bytes(), |i| 2 - i as u8).unwrap();
+    let a = ctx.var(2);
+    let b = ctx.var(1);
+    let c = ctx.var(0);
+    let ab = ctx.xor(a, b);
+    let s = ctx.xor(ab, c);
+    let aab = ctx.and(a, b);
+    let aob = ctx.or(a, b);
+    let ac = ctx.and(aob, c);
+    let cout = ctx.or(aab, ac);
+    assert_eq!(outs, vec![("s".to_string(), s), ("cout".to_string(), cout),
+                          ("one".to_string(), ctx.true_())]);
+
+    // Couverture de l'ensemble OFF.
+    let text = ".inputs a b\n.outputs f\n.names a b f\n11 0\n.end\n";
+    let outs = ctx.read_blif(&mut text.as_bytes(), |i| i as u8).unwrap();
+    let x = ctx.var(0);
+    let y = ctx.var(1);
+    let xy = ctx.and(x, y);
+    assert_eq!(outs[0].1, ctx.not(xy));
+
+    let cycle = ".inputs a\n.outputs f\n.names a g f\n11 1\n.names f g\n1 1\n.end\n";
+    assert!(ctx.read_blif(&mut cycle.as_bytes(), |i| i as u8).is_err());
+    let latch = ".inputs a\n.outputs f\n.latch a f 0\n.end\n";
+    assert!(ctx.read_blif(&mut latch.as_bytes(), |i| i as u8).is_err());
+}
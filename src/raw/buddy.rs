@@ -0,0 +1,143 @@
+// Import et export au format de la fonction `bdd_save` de la bibliothèque
+// BuDDy.
+//
+// Un fichier BuDDy contient un unique BDD. Il commence par le nombre de nœuds
+// et le nombre de variables, suivis de la table `var2level` donnant le niveau
+// de chaque variable dans l'ordre de BuDDy. Viennent ensuite les nœuds, un par
+// ligne, de la forme :
+//     id var low high
+// où `low` et `high` sont les identifiants des fils, 0 et 1 désignant les
+// terminaux FAUX et VRAI. Les fils sont toujours écrits avant leurs pères, et
+// la racine est le dernier nœud du fichier. Un BDD constant est écrit sous la
+// forme `0 0 c`, où `c` vaut 0 ou 1.
+//
+// Comme pour le format DDDMP, les variables sont reliées aux numéros de
+// variables de BuDDy par une correspondance fournie par l'appelant, qui doit
+// être croissante à l'export.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::{self, Read, Write};
+use super::{Bdd, Context, Node, topological, invalid};
+use super::dddmp::parse;
+
+impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
+    // La méthode `write_buddy` écrit le BDD `root` dans `w` au format BuDDy.
+    // `index` associe à chaque variable son numéro de variable BuDDy.
+    pub fn write_buddy<W: Write, F>(&self, w: &mut W, root: Bdd<'arena, V>, index: F)
+                                    -> io::Result<()>
+      where F: Fn(V) -> usize {
+        let nodes = topological(&[root]);
+        match root {
+            Bdd(Node::False) => return writeln!(w, "0 0 0"),
+            Bdd(Node::True) => return writeln!(w, "0 0 1"),
+            Bdd(Node::If { .. }) => ()
+        }
+
+        let varnum = nodes.iter().filter_map(|b| match b {
+            Bdd(Node::If { var, .. }) => Some(index(*var) + 1),
+            _ => None
+        }).max().unwrap_or(0);
+        writeln!(w, "{} {}", nodes.len(), varnum)?;
+        for i in 0..varnum {
+            write!(w, "{} ", i)?;
+        }
+        writeln!(w)?;
+
+        let mut ids: HashMap<Bdd<'arena, V>, usize> = HashMap::new();
+        let id = |ids: &HashMap<Bdd<'arena, V>, usize>, b: Bdd<'arena, V>| match b {
+            Bdd(Node::False) => 0,
+            Bdd(Node::True) => 1,
+            Bdd(Node::If { .. }) => ids[&b],
+        };
+        for (i, &b) in nodes.iter().enumerate() {
+            if let Bdd(Node::If { var, children }) = b {
+                writeln!(w, "{} {} {} {}", i + 2, index(*var),
+                         id(&ids, children[0]), id(&ids, children[1]))?;
+            }
+            ids.insert(b, i + 2);
+        }
+        return Ok(())
+    }
+
+    // La méthode `read_buddy` lit un BDD écrit par la fonction `bdd_save` de
+    // BuDDy. `var` associe à chaque numéro de variable BuDDy la variable
+    // correspondante dans ce contexte.
+    pub fn read_buddy<R: Read, F>(&mut self, r: &mut R, var: F) -> io::Result<Bdd<'arena, V>>
+      where F: Fn(usize) -> V {
+        let mut text = String::new();
+        r.read_to_string(&mut text)?;
+        let mut tokens = text.split_whitespace();
+        let mut next = || -> io::Result<usize> {
+            return parse(tokens.next().ok_or_else(|| invalid("unexpected end of file"))?)
+        };
+
+        let nodenum = next()?;
+        let varnum = next()?;
+        if nodenum == 0 && varnum == 0 {
+            return match next()? {
+                0 => Ok(self.false_()),
+                1 => Ok(self.true_()),
+                _ => Err(invalid("invalid constant BDD"))
+            }
+        }
+        // On ignore la table `var2level` : `branch` reconstruit les nœuds
+        // correctement quel que soit l'ordre des variables du fichier.
+        for _ in 0..varnum {
+            next()?;
+        }
+
+        let mut nodes: HashMap<usize, Bdd<'arena, V>> = HashMap::new();
+        nodes.insert(0, self.false_());
+        nodes.insert(1, self.true_());
+        let mut root = None;
+        for _ in 0..nodenum {
+            // Comme pour le format JSON, les identifiants 0 et 1 sont
+            // réservés aux terminaux, et chaque nœud doit avoir un
+            // identifiant propre.
+            let id = next()?;
+            if id < 2 {
+                return Err(invalid("reserved node id"))
+            }
+            if nodes.contains_key(&id) {
+                return Err(invalid("duplicate node id"))
+            }
+            let v = next()?;
+            if v >= varnum {
+                return Err(invalid("unknown variable"))
+            }
+            let lo = *nodes.get(&next()?).ok_or_else(|| invalid("dangling reference"))?;
+            let hi = *nodes.get(&next()?).ok_or_else(|| invalid("dangling reference"))?;
+            let b = self.branch(var(v), [lo, hi]);
+            nodes.insert(id, b);
+            root = Some(b)
+        }
+        return root.ok_or_else(|| invalid("empty BDD file"))
+    }
+}
+
+#[test]
+fn test_buddy() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let x = ctx.var(0u16);
+    let y = ctx.var(1);
+    let z = ctx.var(2);
+    let xy = ctx.and(x, y);
+    let f = ctx.or(xy, z);
+
+    for &b in &[f, xy, ctx.true_(), ctx.false_()] {
+        let mut buf = Vec::new();
+        ctx.write_buddy(&mut buf, b, |v| v as usize).unwrap();
+        assert_eq!(ctx.read_buddy(&mut &buf[..], |i| i as u16).unwrap(), b);
+    }
+
+    let text = "2 2\n0 1 \n2 1 0 1\n3 0 0 2\n";
+    assert_eq!(ctx.read_buddy(&mut text.as_bytes(), |i| i as u16).unwrap(), xy);
+    assert!(ctx.read_buddy(&mut "1 1\n0\n2 0 0 5\n".as_bytes(), |i| i as u16).is_err());
+
+    // Identifiants réservés aux terminaux, ou en double.
+    for text in ["1 1\n0\n0 0 1 0\n", "1 1\n0\n1 0 1 0\n", "2 1\n0\n2 0 0 1\n2 0 1 0\n"] {
+        assert!(ctx.read_buddy(&mut text.as_bytes(), |i| i as u16).is_err());
+    }
+}
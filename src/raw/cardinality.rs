@@ -0,0 +1,120 @@
+// Contraintes de cardinalité : au plus, au moins ou exactement k variables
+// vraies parmi une liste de variables.
+//
+// Le BDD d'une telle contrainte est construit directement, couche par couche,
+// en parcourant les variables de la dernière à la première. Après avoir lu les
+// i premières variables, le seul état utile est le nombre de variables vraies
+// rencontrées, borné à k + 1 (« plus de k ») : chaque couche compte donc au
+// plus k + 2 nœuds, et le BDD O(n·k) nœuds, sans passer par des conjonctions
+// de clauses.
+
+use std::hash::Hash;
+use super::{Bdd, Context};
+
+impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
+    // La méthode privée `counting` construit le BDD vrai lorsque le nombre
+    // `c` de variables vraies parmi `vars`, borné à `k + 1`, vérifie
+    // `accept(c)`.
+    fn counting<F: Fn(usize) -> bool>(&mut self, k: usize, vars: &[V], accept: F) -> Bdd<'arena, V> {
+        let mut vars = vars.to_vec();
+        vars.sort();
+        vars.dedup();
+        // Le nombre de variables vraies ne dépasse jamais `vars.len()` : on
+        // peut donc borner `k` sans changer le résultat, ce qui évite un
+        // débordement pour `k + 1` et des couches inutilement grandes.
+        // `accept` reste appliquée au compte réel.
+        let k = k.min(vars.len());
+        // `layer[c]` est le BDD de la contrainte sur les variables restantes,
+        // sachant que `c` variables vraies ont déjà été rencontrées.
+        let mut layer: Vec<Bdd<'arena, V>> = (0..=k + 1)
+            .map(|c| if accept(c) { self.true_() } else { self.false_() })
+            .collect();
+        for (i, &v) in vars.iter().enumerate().rev() {
+            // Après i variables, au plus i sont vraies.
+            let states = (k + 1).min(i);
+            layer = (0..=k + 1).map(|c| {
+                if c > states {
+                    return layer[c]
+                }
+                let lo = layer[c];
+                let hi = layer[(c + 1).min(k + 1)];
+                return self.node(v, [lo, hi])
+            }).collect();
+        }
+        return layer[0]
+    }
+
+    // La méthode `at_most` renvoie le BDD vrai lorsqu'au plus `k` des
+    // variables de `vars` sont vraies. Les variables en double ne sont
+    // comptées qu'une fois, de même pour `at_least` et `exactly`.
+    pub fn at_most(&mut self, k: usize, vars: &[V]) -> Bdd<'arena, V> {
+        return self.counting(k, vars, |c| c <= k)
+    }
+
+    // La méthode `at_least` renvoie le BDD vrai lorsqu'au moins `k` des
+    // variables de `vars` sont vraies.
+    pub fn at_least(&mut self, k: usize, vars: &[V]) -> Bdd<'arena, V> {
+        return self.counting(k, vars, |c| c >= k)
+    }
+
+    // La méthode `exactly` renvoie le BDD vrai lorsqu'exactement `k` des
+    // variables de `vars` sont vraies.
+    pub fn exactly(&mut self, k: usize, vars: &[V]) -> Bdd<'arena, V> {
+        return self.counting(k, vars, |c| c == k)
+    }
+}
+
+#[test]
+fn test_cardinality() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let vars: Vec<u16> = (0..10).collect();
+
+    // Le nombre de solutions est un coefficient binomial.
+    let binomial = |n: u64, k: u64| (0..k).fold(1, |acc, i| acc * (n - i) / (i + 1));
+    for k in 0..=11 {
+        let exactly = ctx.exactly(k, &vars).nsat(&vars);
+        assert_eq!(exactly, if k <= 10 { binomial(10, k as u64) } else { 0 });
+        let at_most: u64 = (0..=k.min(10)).map(|i| binomial(10, i as u64)).sum();
+        assert_eq!(ctx.at_most(k, &vars).nsat(&vars), at_most);
+        assert_eq!(ctx.at_least(k, &vars).nsat(&vars), 1024 - at_most + exactly);
+    }
+
+    let x = ctx.var(0);
+    let y = ctx.var(1);
+    let nx = ctx.not(x);
+    let ny = ctx.not(y);
+    let nxy = ctx.and(x, y);
+    assert_eq!(ctx.at_most(1, &[1, 0, 1]), ctx.not(nxy));
+    assert_eq!(ctx.exactly(0, &[0, 1]), ctx.and(nx, ny));
+    assert_eq!(ctx.at_least(1, &[0, 1]), ctx.or(x, y));
+    assert_eq!(ctx.at_least(0, &[]), ctx.true_());
+    assert_eq!(ctx.at_least(1, &[]), ctx.false_());
+}
+
+#[test]
+fn test_cardinality_size() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let vars: Vec<u16> = (0..100).collect();
+    let f = ctx.at_most(3, &vars);
+    assert!(super::topological(&[f]).len() <= 4 * 100);
+}
+
+#[test]
+fn test_cardinality_large_k() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let vars: Vec<u16> = (0..5).collect();
+    let t = ctx.true_();
+    let f = ctx.false_();
+    let all = ctx.at_least(5, &vars);
+    for &k in [5, 6, 1000, usize::MAX].iter() {
+        assert_eq!(ctx.at_most(k, &vars), t);
+        assert_eq!(ctx.at_least(k, &vars), if k == 5 { all } else { f });
+        assert_eq!(ctx.exactly(k, &vars), if k == 5 { all } else { f });
+    }
+    assert_eq!(ctx.at_most(usize::MAX, &[]), t);
+    assert_eq!(ctx.at_least(usize::MAX, &[]), f);
+    assert_eq!(all.nsat(&vars), 1);
+}
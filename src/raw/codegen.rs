@@ -0,0 +1,153 @@
+// Génération de code : compilation d'un BDD en une fonction Rust ou C
+// autonome, qui évalue le BDD sans dépendre de ce module.
+//
+// Le code produit est un programme de branchement qui suit la structure
+// partagée du BDD : chaque nœud interne devient un test sur une case du
+// tableau d'entrées, et n'est écrit qu'une seule fois, même s'il est
+// accessible par plusieurs chemins. Les nœuds sont écrits dans l'ordre
+// topologique inverse (les parents avant leurs fils), la racine en premier ;
+// un arc vers un terminal devient un `return`, et un arc vers un nœud interne
+// un saut en avant vers le code de ce nœud :
+//   - en C, par un `goto` vers une étiquette `n<i>` ;
+//   - en Rust, qui n'a pas de `goto`, par une affectation du numéro `i` du
+//     nœud à une variable d'état `n`, dans une boucle dont le corps choisit
+//     par un `match` le code du nœud courant. Le code reste ainsi plat quelle
+//     que soit la taille du BDD : des blocs imbriqués, un par nœud, se
+//     heurteraient aux limites d'imbrication du compilateur.
+
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::hash::Hash;
+use super::{Bdd, Node, topological};
+
+// Les syntaxes des deux langages cibles.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Lang {
+    Rust,
+    C,
+}
+
+impl<'arena, V: Hash + Copy + Ord> Bdd<'arena, V> {
+    // La méthode privée `codegen` produit le code d'une fonction `name` dans
+    // le langage `lang`. `index` associe à chaque variable sa position dans le
+    // tableau d'entrées.
+    fn codegen<F>(self, lang: Lang, name: &str, index: F) -> String
+      where F: Fn(V) -> usize {
+        let mut nodes = topological(&[self]);
+        nodes.reverse();
+        let ids: HashMap<Bdd<'arena, V>, usize> =
+            nodes.iter().enumerate().map(|(i, &b)| (b, i)).collect();
+        let jump = |b: Bdd<'arena, V>| match (lang, b) {
+            (Lang::Rust, Bdd(Node::False)) => "return false".to_string(),
+            (Lang::Rust, Bdd(Node::True)) => "return true".to_string(),
+            (Lang::Rust, _) => format!("{}", ids[&b]),
+            (Lang::C, Bdd(Node::False)) => "return false;".to_string(),
+            (Lang::C, Bdd(Node::True)) => "return true;".to_string(),
+            (Lang::C, _) => format!("goto n{};", ids[&b]),
+        };
+        let test = |b: Bdd<'arena, V>| match b {
+            Bdd(Node::If { var, children }) => (index(*var), jump(children[1]), jump(children[0])),
+            _ => unreachable!()
+        };
+
+        // L'écriture dans une `String` ne peut pas échouer : on ignore donc
+        // les résultats de `writeln!`.
+        let mut out = String::new();
+        match lang {
+            Lang::Rust => {
+                let _ = writeln!(out, "pub fn {}(x: &[bool]) -> bool {{", name);
+                match nodes.len() {
+                    0 => {
+                        let _ = writeln!(out, "    {}", jump(self));
+                    }
+                    // Un nœud unique n'a pas besoin de boucle, et son `match`
+                    // ne contiendrait que des branches qui ne terminent pas.
+                    1 => {
+                        let (i, hi, lo) = test(self);
+                        let _ = writeln!(out, "    if x[{}] {{ {} }} else {{ {} }}", i, hi, lo);
+                    }
+                    _ => {
+                        let _ = writeln!(out, "    let mut n = 0;");
+                        let _ = writeln!(out, "    loop {{");
+                        let _ = writeln!(out, "        n = match n {{");
+                        for (k, &b) in nodes.iter().enumerate() {
+                            let (i, hi, lo) = test(b);
+                            let _ = writeln!(out, "            {} => if x[{}] {{ {} }} else {{ {} }},",
+                                             k, i, hi, lo);
+                        }
+                        let _ = writeln!(out, "            _ => unreachable!(),");
+                        let _ = writeln!(out, "        }}");
+                        let _ = writeln!(out, "    }}");
+                    }
+                }
+            }
+            Lang::C => {
+                let _ = writeln!(out, "bool {}(const bool *x) {{", name);
+                if nodes.is_empty() {
+                    let _ = writeln!(out, "    {}", jump(self));
+                }
+                for (k, &b) in nodes.iter().enumerate() {
+                    let (i, hi, lo) = test(b);
+                    let label = if k > 0 { format!("n{}: ", k) } else { String::new() };
+                    let _ = writeln!(out, "    {}if (x[{}]) {} else {}", label, i, hi, lo);
+                }
+            }
+        }
+        let _ = writeln!(out, "}}");
+        return out
+    }
+
+    // La méthode `to_rust` renvoie le code source d'une fonction Rust
+    // `pub fn <name>(x: &[bool]) -> bool` qui évalue le BDD, la variable `v`
+    // étant lue dans `x[index(v)]`.
+    pub fn to_rust<F>(self, name: &str, index: F) -> String
+      where F: Fn(V) -> usize {
+        return self.codegen(Lang::Rust, name, index)
+    }
+
+    // La méthode `to_c` renvoie le code source d'une fonction C
+    // `bool <name>(const bool *x)`, de la même manière que `to_rust`. Le code
+    // produit requiert l'en-tête `<stdbool.h>`.
+    pub fn to_c<F>(self, name: &str, index: F) -> String
+      where F: Fn(V) -> usize {
+        return self.codegen(Lang::C, name, index)
+    }
+}
+
+#[test]
+fn test_codegen() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = super::Context::new(&allo);
+    let x = ctx.var(0u8);
+    let y = ctx.var(1);
+    let z = ctx.var(2);
+    let xy = ctx.and(x, y);
+    let f = ctx.or(xy, z);
+
+    // Le nœud z n'apparaît qu'une fois, bien qu'il soit atteint par deux
+    // chemins.
+    assert_eq!(f.to_rust("f", |v| v as usize), "\
+pub fn f(x: &[bool]) -> bool {
+    let mut n = 0;
+    loop {
+        n = match n {
+            0 => if x[0] { 1 } else { 2 },
+            1 => if x[1] { return true } else { 2 },
+            2 => if x[2] { return true } else { return false },
+            _ => unreachable!(),
+        }
+    }
+}
+");
+    assert_eq!(f.to_c("f", |v| 2 - v as usize), "\
+bool f(const bool *x) {
+    if (x[2]) goto n1; else goto n2;
+    n1: if (x[1]) return true; else goto n2;
+    n2: if (x[0]) return true; else return false;
+}
+");
+    assert_eq!(ctx.true_().to_rust("t", |v| v as usize),
+               "pub fn t(x: &[bool]) -> bool {\n    return true\n}\n");
+    assert_eq!(z.to_rust("z", |v| v as usize),
+               "pub fn z(x: &[bool]) -> bool {\n    if x[2] { return true } else { return false }\n}\n");
+}
@@ -0,0 +1,262 @@
+// Import et export au format texte DDDMP, utilisé par la bibliothèque CUDD.
+//
+// Un fichier DDDMP commence par un en-tête composé de lignes de la forme
+// `.clé valeurs...`, suivi de la section `.nodes`, qui se termine par `.end`.
+// Chaque ligne de la section `.nodes` décrit un nœud :
+//     id info var then else
+// où `info` est une information sur la variable (son indice CUDD, dans les
+// fichiers que l'on produit, avec `.varinfo 0`), `var` est la position de la
+// variable dans la liste `.ids` du support, et `then` et `else` sont les
+// identifiants des fils. CUDD utilise des arcs complémentés : un identifiant
+// négatif désigne la négation du nœud correspondant, et il n'existe qu'un seul
+// nœud terminal (VRAI), décrit par la ligne `id T 1 0 0`. Par convention,
+// l'arc `then` n'est jamais complémenté.
+//
+// Nos BDDs n'ont pas d'arcs complémentés : l'export les calcule, et l'import
+// les supprime en reconstruisant les nœuds avec `Context::node` (via
+// `Context::branch`), ce qui préserve les invariants du contexte.
+//
+// Les variables sont reliées aux indices de variables de CUDD par une
+// correspondance fournie par l'appelant. À l'export, cette correspondance doit
+// être croissante, afin que l'ordre des variables de CUDD soit celui de `V`.
+// Seul le mode texte (`.mode A`) est géré.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::{self, Read, Write};
+use std::str::FromStr;
+use super::{Bdd, Context, Node, topological, support, invalid};
+
+// La fonction `parse` lit un entier dans un fichier DDDMP ou BuDDy.
+pub(crate) fn parse<T: FromStr>(s: &str) -> io::Result<T> {
+    return s.parse().map_err(|_| invalid("expected an integer"))
+}
+
+impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
+    // La méthode `write_dddmp` écrit dans `w` les BDDs de `roots` au format
+    // DDDMP. `index` associe à chaque variable son indice CUDD. Les noms des
+    // racines ne doivent pas contenir d'espaces.
+    pub fn write_dddmp<W: Write, F>(&self, w: &mut W, roots: &[(&str, Bdd<'arena, V>)], index: F)
+                                    -> io::Result<()>
+      where F: Fn(V) -> usize {
+        let bdds: Vec<Bdd<'arena, V>> = roots.iter().map(|&(_, b)| b).collect();
+        let nodes = topological(&bdds);
+
+        let vars = support(&nodes);
+        let supp: HashMap<V, usize> = vars.iter().enumerate().map(|(i, &v)| (v, i)).collect();
+
+        // `ids` associe à chaque nœud son identifiant signé (négatif lorsque
+        // l'arc est complémenté), et `unique` associe à chaque nœud CUDD
+        // (régulier) son identifiant.
+        let mut ids: HashMap<Bdd<'arena, V>, i64> = HashMap::new();
+        let mut unique: HashMap<(usize, i64, i64), i64> = HashMap::new();
+        let mut lines = Vec::new();
+        let sid = |ids: &HashMap<Bdd<'arena, V>, i64>, b: Bdd<'arena, V>| match b {
+            Bdd(Node::False) => -1,
+            Bdd(Node::True) => 1,
+            Bdd(Node::If { .. }) => ids[&b],
+        };
+        for &b in &nodes {
+            if let Bdd(Node::If { var, children }) = b {
+                let lo = sid(&ids, children[0]);
+                let hi = sid(&ids, children[1]);
+                let (key, neg) = if hi < 0 {
+                    ((supp[var], -hi, -lo), true)
+                } else {
+                    ((supp[var], hi, lo), false)
+                };
+                let next = unique.len() as i64 + 2;
+                let id = *unique.entry(key).or_insert_with(|| {
+                    lines.push(format!("{} {} {} {} {}", next, index(*var), key.0, key.1, key.2));
+                    next
+                });
+                ids.insert(b, if neg { -id } else { id });
+            }
+        }
+
+        let indices: Vec<String> = vars.iter().map(|&v| index(v).to_string()).collect();
+        let nvars = vars.iter().map(|&v| index(v) + 1).max().unwrap_or(0);
+        let rootids: Vec<String> = bdds.iter().map(|&b| sid(&ids, b).to_string()).collect();
+        let rootnames: Vec<&str> = roots.iter().map(|&(name, _)| name).collect();
+
+        writeln!(w, ".ver DDDMP-2.0")?;
+        writeln!(w, ".mode A")?;
+        writeln!(w, ".varinfo 0")?;
+        writeln!(w, ".nnodes {}", lines.len() + 1)?;
+        writeln!(w, ".nvars {}", nvars)?;
+        writeln!(w, ".nsuppvars {}", vars.len())?;
+        writeln!(w, ".ids {}", indices.join(" "))?;
+        writeln!(w, ".permids {}", indices.join(" "))?;
+        writeln!(w, ".nroots {}", roots.len())?;
+        writeln!(w, ".rootids {}", rootids.join(" "))?;
+        writeln!(w, ".rootnames {}", rootnames.join(" "))?;
+        writeln!(w, ".nodes")?;
+        writeln!(w, "1 T 1 0 0")?;
+        for line in &lines {
+            writeln!(w, "{}", line)?;
+        }
+        writeln!(w, ".end")?;
+        return Ok(())
+    }
+
+    // La méthode `read_dddmp` lit un fichier DDDMP en mode texte, et renvoie
+    // les racines qu'il contient avec leurs noms (vides si le fichier ne
+    // contient pas de section `.rootnames`). `var` associe à chaque indice
+    // de variable CUDD la variable correspondante dans ce contexte.
+    pub fn read_dddmp<R: Read, F>(&mut self, r: &mut R, var: F)
+                                  -> io::Result<Vec<(String, Bdd<'arena, V>)>>
+      where F: Fn(usize) -> V {
+        let mut text = String::new();
+        r.read_to_string(&mut text)?;
+
+        let mut ids: Option<Vec<usize>> = None;
+        let mut rootids: Vec<i64> = Vec::new();
+        let mut rootnames: Vec<String> = Vec::new();
+        let mut nodes: HashMap<i64, Bdd<'arena, V>> = HashMap::new();
+        let mut in_nodes = false;
+        let mut ended = false;
+
+        for line in text.lines() {
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            if tokens.is_empty() {
+                continue
+            }
+            if !in_nodes {
+                match tokens[0] {
+                    ".mode" if tokens.get(1) != Some(&"A") =>
+                        return Err(invalid("only the text mode of DDDMP is supported")),
+                    ".ids" =>
+                        ids = Some(tokens[1..].iter().map(|t| parse(t)).collect::<io::Result<_>>()?),
+                    ".rootids" =>
+                        rootids = tokens[1..].iter().map(|t| parse(t)).collect::<io::Result<_>>()?,
+                    ".rootnames" =>
+                        rootnames = tokens[1..].iter().map(|t| t.to_string()).collect(),
+                    ".nodes" => in_nodes = true,
+                    _ => ()
+                }
+                continue
+            }
+            if tokens[0] == ".end" {
+                ended = true;
+                break
+            }
+
+            // Les identifiants sont positifs (leur signe marque les arcs
+            // complémentés), et chaque nœud, terminal compris, doit avoir un
+            // identifiant propre.
+            let id: i64 = parse(tokens[0])?;
+            if id <= 0 {
+                return Err(invalid("invalid node id"))
+            }
+            if nodes.contains_key(&id) {
+                return Err(invalid("duplicate node id"))
+            }
+            if tokens.get(1) == Some(&"T") {
+                nodes.insert(id, self.true_());
+                continue
+            }
+            if tokens.len() < 4 {
+                return Err(invalid("malformed node line"))
+            }
+            let n = tokens.len();
+            let idx: usize = parse(tokens[n - 3])?;
+            let index = match &ids {
+                Some(ids) => *ids.get(idx).ok_or_else(|| invalid("unknown variable"))?,
+                None => idx
+            };
+            let hi = self.dddmp_edge(&nodes, parse(tokens[n - 2])?)?;
+            let lo = self.dddmp_edge(&nodes, parse(tokens[n - 1])?)?;
+            let b = self.branch(var(index), [lo, hi]);
+            nodes.insert(id, b);
+        }
+        if !ended {
+            return Err(invalid("missing .end"))
+        }
+
+        let mut roots = Vec::new();
+        for (i, &id) in rootids.iter().enumerate() {
+            let name = rootnames.get(i).cloned().unwrap_or_default();
+            roots.push((name, self.dddmp_edge(&nodes, id)?))
+        }
+        return Ok(roots)
+    }
+
+    // La méthode privée `dddmp_edge` renvoie le BDD désigné par un
+    // identifiant signé d'un fichier DDDMP.
+    fn dddmp_edge(&mut self, nodes: &HashMap<i64, Bdd<'arena, V>>, id: i64)
+                  -> io::Result<Bdd<'arena, V>> {
+        let b = *nodes.get(&id.abs()).ok_or_else(|| invalid("dangling reference"))?;
+        if id < 0 {
+            return Ok(self.not(b))
+        }
+        return Ok(b)
+    }
+}
+
+#[test]
+fn test_dddmp_roundtrip() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let x = ctx.var(0u16);
+    let y = ctx.var(1);
+    let z = ctx.var(2);
+    let xy = ctx.and(x, y);
+    let f = ctx.xor(xy, z);
+    let nf = ctx.not(f);
+    let t = ctx.true_();
+    let fa = ctx.false_();
+
+    let mut buf = Vec::new();
+    ctx.write_dddmp(&mut buf, &[("f", f), ("nf", nf), ("t", t), ("fa", fa)], |v| v as usize * 2)
+        .unwrap();
+    let text = String::from_utf8(buf.clone()).unwrap();
+    // `f` et sa négation partagent les mêmes nœuds CUDD.
+    assert!(text.contains(".nnodes 4\n"));
+    assert!(text.contains(".ids 0 2 4\n"));
+
+    let roots = ctx.read_dddmp(&mut &buf[..], |i| i as u16 / 2).unwrap();
+    assert_eq!(roots, vec![("f".to_string(), f), ("nf".to_string(), nf),
+                           ("t".to_string(), t), ("fa".to_string(), fa)]);
+}
+
+#[test]
+fn test_dddmp_complemented() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    // x0 xor x1, tel que produit par CUDD.
+    let text = "\
+.ver DDDMP-2.0
+.mode A
+.varinfo 0
+.nnodes 3
+.nvars 2
+.nsuppvars 2
+.ids 0 1
+.permids 0 1
+.nroots 1
+.rootids -3
+.nodes
+1 T 1 0 0
+2 1 1 1 -1
+3 0 0 2 -2
+.end
+";
+    let roots = ctx.read_dddmp(&mut text.as_bytes(), |i| i as u8).unwrap();
+    let x = ctx.var(0);
+    let y = ctx.var(1);
+    assert_eq!(roots, vec![(String::new(), ctx.xor(x, y))]);
+
+    // Une correspondance qui inverse l'ordre des variables est acceptée.
+    let roots = ctx.read_dddmp(&mut text.as_bytes(), |i| 1 - i as u8).unwrap();
+    assert_eq!(roots[0].1, ctx.xor(x, y));
+
+    assert!(ctx.read_dddmp(&mut ".nodes\n2 0 0 3 1\n.end\n".as_bytes(), |i| i as u8).is_err());
+    assert!(ctx.read_dddmp(&mut ".mode B\n".as_bytes(), |i| i as u8).is_err());
+
+    // Identifiants nuls, ou en double (y compris celui du terminal).
+    for nodes in ["0 T 1 0 0\n", "1 T 1 0 0\n2 0 0 1 -1\n2 1 1 1 -1\n",
+                  "1 T 1 0 0\n1 0 0 1 -1\n"] {
+        let text = format!(".nodes\n{}.end\n", nodes);
+        assert!(ctx.read_dddmp(&mut text.as_bytes(), |i| i as u8).is_err());
+    }
+}
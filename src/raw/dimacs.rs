@@ -0,0 +1,273 @@
+// Lecture et écriture de formules en forme normale conjonctive au format
+// DIMACS CNF, et construction du BDD correspondant.
+//
+// Un fichier DIMACS commence par des lignes de commentaires (commençant par
+// `c`), suivies d'une ligne `p cnf <variables> <clauses>`. Les clauses sont
+// ensuite données comme des suites d'entiers non nuls terminées par 0 : un
+// entier `i` positif désigne la variable numéro `i`, et `-i` sa négation.
+//
+// Construire le BDD d'une CNF en effectuant la conjonction des clauses les unes
+// après les autres produit souvent des BDDs intermédiaires énormes. On
+// regroupe donc les clauses par paquets selon leur plus petite variable, on
+// effectue la conjonction de chaque paquet, puis on combine les paquets deux à
+// deux, en suivant l'ordre des variables, selon un arbre équilibré.
+//
+// Inversement, `to_cnf` produit une CNF équisatisfiable à un ensemble de BDDs,
+// par l'encodage de Tseitin : chaque nœud interne reçoit une variable
+// auxiliaire, contrainte à être égale à la valeur du nœud.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::io::{self, Read, Write};
+use super::{Bdd, Context, Node, topological, support, invalid};
+use super::dddmp::parse;
+
+// Une formule en forme normale conjonctive. Les variables sont numérotées à
+// partir de 1, et chaque clause est une liste de littéraux non nuls.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cnf {
+    pub nvars: usize,
+    pub clauses: Vec<Vec<i64>>,
+}
+
+impl Cnf {
+    // La méthode `read` lit une formule au format DIMACS.
+    pub fn read<R: Read>(r: &mut R) -> io::Result<Cnf> {
+        let mut text = String::new();
+        r.read_to_string(&mut text)?;
+
+        let mut nvars = None;
+        let mut clauses = Vec::new();
+        let mut clause = Vec::new();
+        for line in text.lines() {
+            let line = line.trim();
+            if line.starts_with('c') || line.is_empty() {
+                continue
+            }
+            // Certains jeux de tests (SATLIB) terminent le fichier par `%`.
+            if line.starts_with('%') {
+                break
+            }
+            if line.starts_with('p') {
+                let tokens: Vec<&str> = line.split_whitespace().collect();
+                if tokens.len() != 4 || tokens[1] != "cnf" || nvars.is_some() {
+                    return Err(invalid("malformed problem line"))
+                }
+                nvars = Some(parse(tokens[2])?);
+                continue
+            }
+            let nvars = nvars.ok_or_else(|| invalid("missing problem line"))?;
+            for t in line.split_whitespace() {
+                let lit: i64 = parse(t)?;
+                if lit == 0 {
+                    clauses.push(std::mem::take(&mut clause))
+                } else if lit.unsigned_abs() as usize > nvars {
+                    return Err(invalid("literal out of range"))
+                } else {
+                    clause.push(lit)
+                }
+            }
+        }
+        if !clause.is_empty() {
+            clauses.push(clause)
+        }
+        let nvars = nvars.ok_or_else(|| invalid("missing problem line"))?;
+        return Ok(Cnf { nvars, clauses })
+    }
+
+    // La méthode `write` écrit la formule au format DIMACS.
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "p cnf {} {}", self.nvars, self.clauses.len())?;
+        for clause in &self.clauses {
+            for lit in clause {
+                write!(w, "{} ", lit)?;
+            }
+            writeln!(w, "0")?;
+        }
+        return Ok(())
+    }
+}
+
+impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
+    // La méthode `clause` construit directement le BDD d'une clause, sans
+    // passer par des disjonctions. `var` associe à chaque numéro de variable
+    // DIMACS la variable correspondante.
+    pub fn clause<F>(&mut self, lits: &[i64], var: F) -> Bdd<'arena, V>
+      where F: Fn(usize) -> V {
+        let lits: Vec<(V, bool)> =
+            lits.iter().map(|&l| (var(l.unsigned_abs() as usize), l > 0)).collect();
+        return self.literals(&lits, false)
+    }
+
+    // La méthode privée `and_balanced` calcule la conjonction des BDDs de
+    // `bdds`, en les combinant deux à deux selon un arbre équilibré.
+    fn and_balanced(&mut self, mut bdds: Vec<Bdd<'arena, V>>) -> Bdd<'arena, V> {
+        if bdds.is_empty() {
+            return self.true_()
+        }
+        while bdds.len() > 1 {
+            let mut next = Vec::with_capacity(bdds.len().div_ceil(2));
+            for pair in bdds.chunks(2) {
+                let b = if pair.len() == 2 { self.and(pair[0], pair[1]) } else { pair[0] };
+                if b == self.false_() {
+                    return b
+                }
+                next.push(b)
+            }
+            bdds = next
+        }
+        return bdds[0]
+    }
+
+    // La méthode `cnf` construit le BDD de la formule `cnf`.
+    pub fn cnf<F>(&mut self, cnf: &Cnf, var: F) -> Bdd<'arena, V>
+      where F: Fn(usize) -> V {
+        // Les clauses constantes (vraies) ne sont dans aucun paquet, et une
+        // clause fausse rend toute la formule fausse.
+        let mut buckets: BTreeMap<V, Vec<Bdd<'arena, V>>> = BTreeMap::new();
+        for c in &cnf.clauses {
+            match self.clause(c, &var) {
+                Bdd(Node::True) => (),
+                Bdd(Node::False) => return self.false_(),
+                b @ Bdd(Node::If { var, .. }) => buckets.entry(*var).or_default().push(b)
+            }
+        }
+        let mut parts = Vec::new();
+        for (_, bucket) in buckets.into_iter().rev() {
+            parts.push(self.and_balanced(bucket))
+        }
+        return self.and_balanced(parts)
+    }
+
+    // La méthode `read_dimacs` lit une formule au format DIMACS et construit
+    // son BDD.
+    pub fn read_dimacs<R: Read, F>(&mut self, r: &mut R, var: F) -> io::Result<Bdd<'arena, V>>
+      where F: Fn(usize) -> V {
+        let cnf = Cnf::read(r)?;
+        return Ok(self.cnf(&cnf, var))
+    }
+
+    // La méthode `to_cnf` renvoie une CNF équisatisfiable à la conjonction
+    // des BDDs de `roots`. Les variables des BDDs reçoivent les numéros DIMACS
+    // 1 à k, dans l'ordre croissant, et le vecteur renvoyé donne la variable
+    // correspondant à chacun de ces numéros (le numéro `i` correspondant à
+    // l'indice `i - 1`). Les numéros suivants sont ceux des variables
+    // auxiliaires, une par nœud interne. Chaque affectation des variables des
+    // BDDs satisfaisant les racines s'étend de manière unique en un modèle de
+    // la CNF.
+    pub fn to_cnf(&self, roots: &[Bdd<'arena, V>]) -> (Cnf, Vec<V>) {
+        let nodes = topological(roots);
+        let vars = support(&nodes);
+        let var_ids: HashMap<V, i64> =
+            vars.iter().enumerate().map(|(i, &v)| (v, i as i64 + 1)).collect();
+        let aux: HashMap<Bdd<'arena, V>, i64> = nodes.iter().enumerate()
+            .map(|(i, &b)| (b, (vars.len() + i) as i64 + 1)).collect();
+
+        // Un littéral est soit une constante, soit un numéro DIMACS signé.
+        let lit = |b: Bdd<'arena, V>| match b {
+            Bdd(Node::False) => Err(false),
+            Bdd(Node::True) => Err(true),
+            Bdd(Node::If { .. }) => Ok(aux[&b]),
+        };
+        // La fonction `push` ajoute une clause, en simplifiant les littéraux
+        // constants.
+        let push = |clauses: &mut Vec<Vec<i64>>, lits: &[Result<i64, bool>]| {
+            let mut clause = Vec::new();
+            for l in lits {
+                match *l {
+                    Err(true) => return,
+                    Err(false) => (),
+                    Ok(l) => clause.push(l)
+                }
+            }
+            clauses.push(clause)
+        };
+        let neg = |l: Result<i64, bool>| match l {
+            Err(b) => Err(!b),
+            Ok(l) => Ok(-l)
+        };
+
+        let mut clauses = Vec::new();
+        for &b in &nodes {
+            if let Bdd(Node::If { var, children }) = b {
+                // n <-> (x ? h : l)
+                let n = Ok(aux[&b]);
+                let x = Ok(var_ids[var]);
+                let l = lit(children[0]);
+                let h = lit(children[1]);
+                push(&mut clauses, &[neg(x), neg(h), n]);
+                push(&mut clauses, &[neg(x), h, neg(n)]);
+                push(&mut clauses, &[x, neg(l), n]);
+                push(&mut clauses, &[x, l, neg(n)]);
+            }
+        }
+        for &r in roots {
+            push(&mut clauses, &[lit(r)])
+        }
+        return (Cnf { nvars: vars.len() + nodes.len(), clauses }, vars)
+    }
+}
+
+#[test]
+fn test_read_cnf() {
+    let text = "c exemple\np cnf 3 2\n1 -3 0\n2 3\n-1 0\n%\n0\n";
+    let cnf = Cnf::read(&mut text.as_bytes()).unwrap();
+    assert_eq!(cnf, Cnf { nvars: 3, clauses: vec![vec![1, -3], vec![2, 3, -1]] });
+    let mut buf = Vec::new();
+    cnf.write(&mut buf).unwrap();
+    assert_eq!(Cnf::read(&mut &buf[..]).unwrap(), cnf);
+
+    assert!(Cnf::read(&mut "1 2 0\n".as_bytes()).is_err());
+    assert!(Cnf::read(&mut "p cnf 2 1\n1 3 0\n".as_bytes()).is_err());
+}
+
+#[test]
+fn test_dimacs() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let var = |i: usize| i as u16 - 1;
+    let x = ctx.var(0);
+    let y = ctx.var(1);
+    let z = ctx.var(2);
+
+    assert_eq!(ctx.clause(&[1, -3], var), { let nz = ctx.not(z); ctx.or(x, nz) });
+    assert_eq!(ctx.clause(&[3, 1, 3], var), ctx.or(x, z));
+    assert_eq!(ctx.clause(&[2, -2], var), ctx.true_());
+    assert_eq!(ctx.clause(&[], var), ctx.false_());
+
+    let text = "p cnf 3 3\n1 2 0\n-1 2 0\n-2 3 0\n";
+    let f = ctx.read_dimacs(&mut text.as_bytes(), var).unwrap();
+    assert_eq!(f, ctx.and(y, z));
+    assert_eq!(f.nsat(&[0, 1, 2]), 2);
+
+    // Le principe des tiroirs : 3 pigeons ne tiennent pas dans 2 trous.
+    let text = "p cnf 6 9\n1 2 0\n3 4 0\n5 6 0\n\
+                -1 -3 0\n-1 -5 0\n-3 -5 0\n-2 -4 0\n-2 -6 0\n-4 -6 0\n";
+    assert_eq!(ctx.read_dimacs(&mut text.as_bytes(), var).unwrap(), ctx.false_());
+}
+
+#[test]
+fn test_to_cnf() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let x = ctx.var(3u16);
+    let y = ctx.var(5);
+    let z = ctx.var(8);
+    let xy = ctx.and(x, y);
+    let f = ctx.xor(xy, z);
+    let g = ctx.or(x, z);
+
+    let (cnf, vars) = ctx.to_cnf(&[f, g]);
+    assert_eq!(vars, vec![3, 5, 8]);
+    // Les variables auxiliaires étant déterminées par les variables des BDDs,
+    // la CNF a autant de modèles que la conjonction des racines.
+    let all: Vec<u16> = (0..cnf.nvars as u16).collect();
+    let b = ctx.cnf(&cnf, |i| i as u16 - 1);
+    let fg = ctx.and(f, g);
+    assert_eq!(b.nsat(&all), fg.nsat(&[3, 5, 8]));
+
+    let t = ctx.true_();
+    let fa = ctx.false_();
+    assert_eq!(ctx.to_cnf(&[t]).0, Cnf { nvars: 0, clauses: vec![] });
+    assert_eq!(ctx.to_cnf(&[fa]).0, Cnf { nvars: 0, clauses: vec![vec![]] });
+}
@@ -0,0 +1,128 @@
+// Export des BDDs au format DOT de Graphviz.
+//
+// Le graphe produit représente le DAG partagé de tous les BDDs passés en
+// paramètre : chaque nœud n'apparaît qu'une seule fois, même s'il est
+// accessible depuis plusieurs racines. Les nœuds internes sont étiquetés par
+// leur variable, le fils `children[0]` (variable fausse) est relié par un arc
+// en pointillés et le fils `children[1]` (variable vraie) par un arc plein. Les
+// nœuds terminaux sont dessinés comme des boîtes.
+
+use std::collections::HashMap;
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+use super::{Bdd, Context, Node, topological};
+
+// La fonction `dot_id` renvoie l'identifiant DOT d'un nœud : les terminaux ont
+// des identifiants fixes, les nœuds internes sont numérotés par `ids`.
+fn dot_id<'arena, V>(b: Bdd<'arena, V>, ids: &HashMap<Bdd<'arena, V>, usize>) -> String {
+    match b {
+        Bdd(Node::False) => "f".to_string(),
+        Bdd(Node::True) => "t".to_string(),
+        Bdd(Node::If { .. }) => format!("n{}", ids[&b]),
+    }
+}
+
+// La fonction `quote` renvoie l'étiquette `s` sous forme de chaîne DOT entre
+// guillemets. Seuls les guillemets et les barres obliques inverses sont échappés.
+fn quote(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        if c == '"' || c == '\\' {
+            out.push('\\')
+        }
+        out.push(c)
+    }
+    out.push('"');
+    return out
+}
+
+impl<'arena, V: Hash + Copy + Ord + Display> Context<'arena, V> {
+    // La méthode `to_dot` renvoie une description au format DOT des BDDs de
+    // `roots`. Si `names` contient un nom pour la i-ème racine, une étiquette
+    // portant ce nom pointe vers celle-ci. `names` peut être plus court que
+    // `roots` (voire vide) : les racines restantes ne sont alors pas nommées.
+    pub fn to_dot(&self, roots: &[Bdd<'arena, V>], names: &[&str]) -> String {
+        let nodes = topological(roots);
+        let ids: HashMap<Bdd<'arena, V>, usize> =
+            nodes.iter().enumerate().map(|(i, &b)| (b, i)).collect();
+
+        // On ne dessine que les terminaux effectivement atteints.
+        let mut has_false = false;
+        let mut has_true = false;
+        let mut note_terminal = |b: Bdd<'arena, V>| match b {
+            Bdd(Node::False) => has_false = true,
+            Bdd(Node::True) => has_true = true,
+            Bdd(Node::If { .. }) => ()
+        };
+        for &r in roots {
+            note_terminal(r)
+        }
+        for b in &nodes {
+            if let Bdd(Node::If { children, .. }) = b {
+                note_terminal(children[0]);
+                note_terminal(children[1])
+            }
+        }
+
+        // L'écriture dans une `String` ne peut pas échouer : on ignore donc
+        // les résultats de `writeln!`.
+        let mut out = String::new();
+        let _ = writeln!(out, "digraph bdd {{");
+        if has_false {
+            let _ = writeln!(out, "  f [shape=box, label=\"0\"];");
+        }
+        if has_true {
+            let _ = writeln!(out, "  t [shape=box, label=\"1\"];");
+        }
+        for &b in &nodes {
+            if let Bdd(Node::If { var, children }) = b {
+                let id = dot_id(b, &ids);
+                let _ = writeln!(out, "  {} [shape=circle, label={}];", id, quote(&var.to_string()));
+                let _ = writeln!(out, "  {} -> {} [style=dashed];", id, dot_id(children[0], &ids));
+                let _ = writeln!(out, "  {} -> {};", id, dot_id(children[1], &ids));
+            }
+        }
+        for (i, &r) in roots.iter().enumerate() {
+            if let Some(name) = names.get(i) {
+                let _ = writeln!(out, "  r{} [shape=plaintext, label={}];", i, quote(name));
+                let _ = writeln!(out, "  r{} -> {};", i, dot_id(r, &ids));
+            }
+        }
+        let _ = writeln!(out, "}}");
+        return out
+    }
+}
+
+#[test]
+fn test_to_dot() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let x = ctx.var(0);
+    let y = ctx.var(1);
+    let xy = ctx.and(x, y);
+    let xoy = ctx.or(x, y);
+
+    let dot = ctx.to_dot(&[xy, xoy], &["f", "g"]);
+    assert!(dot.starts_with("digraph bdd {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("  f [shape=box, label=\"0\"];"));
+    assert!(dot.contains("  t [shape=box, label=\"1\"];"));
+    assert!(dot.contains("  r0 [shape=plaintext, label=\"f\"];"));
+    assert!(dot.contains("  r1 [shape=plaintext, label=\"g\"];"));
+    // Le nœud `y` est partagé entre les deux racines : il n'y a donc que trois
+    // nœuds internes.
+    assert_eq!(dot.matches("shape=circle").count(), 3);
+    assert_eq!(dot.matches("style=dashed").count(), 3);
+
+    let dot = ctx.to_dot(&[x], &[]);
+    assert!(!dot.contains("shape=plaintext"));
+    let t = ctx.true_();
+    let dot = ctx.to_dot(&[t], &["t"]);
+    assert!(!dot.contains("label=\"0\""));
+    assert!(dot.contains("  r0 -> t;"));
+
+    // Les étiquettes sont échappées selon les règles de DOT, et non de Rust.
+    let dot = ctx.to_dot(&[t], &["a\"b\\c\u{e9}\n"]);
+    assert!(dot.contains("  r0 [shape=plaintext, label=\"a\\\"b\\\\c\u{e9}\n\"];"));
+}
@@ -0,0 +1,126 @@
+// Inspection en lecture seule de la structure des BDDs.
+//
+// Le type `Node` reste privé, afin de garantir les invariants de réduction et
+// d'unicité des BDDs. Les méthodes de ce module exposent néanmoins la racine
+// d'un BDD : sa variable et ses fils, ou le terminal qu'il représente. Le
+// type `NodeView` permet de filtrer cette racine avec `match` ; il est
+// paramétré par le type `B` des fils, pour servir aussi à l'interface de
+// haut niveau.
+//
+// La méthode `fold` calcule une valeur par récurrence sur la structure du
+// BDD, des feuilles vers la racine, en ne visitant qu'une fois chaque nœud
+// partagé.
+
+use std::collections::hash_map::HashMap;
+use std::hash::Hash;
+use super::{Bdd, Node};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NodeView<V, B> {
+    False, True,
+    If { var: V, low: B, high: B }
+}
+
+impl<'arena, V: Hash + Copy + Ord> Bdd<'arena, V> {
+    // La méthode `view` renvoie la racine de `self`.
+    pub fn view(self) -> NodeView<V, Bdd<'arena, V>> {
+        match self.0 {
+            Node::False => NodeView::False,
+            Node::True => NodeView::True,
+            Node::If { var, children } => NodeView::If { var: *var, low: children[0], high: children[1] }
+        }
+    }
+
+    pub fn is_true(self) -> bool {
+        return matches!(self.0, Node::True)
+    }
+
+    pub fn is_false(self) -> bool {
+        return matches!(self.0, Node::False)
+    }
+
+    // Les méthodes `top_var`, `low` et `high` renvoient la variable de la
+    // racine et ses fils pour la valeur fausse et vraie de celle-ci, ou
+    // `None` si `self` est un terminal.
+    pub fn top_var(self) -> Option<V> {
+        match self.0 {
+            Node::If { var, .. } => Some(*var),
+            _ => None
+        }
+    }
+
+    pub fn low(self) -> Option<Bdd<'arena, V>> {
+        match self.0 {
+            Node::If { children, .. } => Some(children[0]),
+            _ => None
+        }
+    }
+
+    pub fn high(self) -> Option<Bdd<'arena, V>> {
+        match self.0 {
+            Node::If { children, .. } => Some(children[1]),
+            _ => None
+        }
+    }
+
+    // La méthode `fold` calcule `leaf(b)` pour les terminaux, et
+    // `node(var, low, high)` pour les nœuds internes à partir des valeurs de
+    // leurs fils. Les nœuds sont traités dans l'ordre topologique, sans
+    // récursion, et la valeur de chaque nœud est calculée une seule fois.
+    pub fn fold<T, L, N>(self, leaf: L, node: N) -> T
+      where T: Clone, L: Fn(bool) -> T, N: Fn(V, &T, &T) -> T {
+        let mut values: HashMap<Bdd<'arena, V>, T> = HashMap::new();
+        let value = |b: Bdd<'arena, V>, values: &HashMap<Bdd<'arena, V>, T>| match b.0 {
+            Node::False => leaf(false),
+            Node::True => leaf(true),
+            Node::If { .. } => values[&b].clone()
+        };
+        for b in super::topological(&[self]) {
+            if let Node::If { var, children } = b.0 {
+                let lo = value(children[0], &values);
+                let hi = value(children[1], &values);
+                let res = node(*var, &lo, &hi);
+                values.insert(b, res);
+            }
+        }
+        return value(self, &values)
+    }
+}
+
+#[test]
+fn test_inspect() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = super::Context::new(&allo);
+    let x = ctx.var(0);
+    let y = ctx.var(1);
+    let f = ctx.xor(x, y);
+    let ny = ctx.not(y);
+    let t = ctx.true_();
+    let fl = ctx.false_();
+
+    assert!(t.is_true() && fl.is_false() && !f.is_true() && !f.is_false());
+    assert_eq!(f.top_var(), Some(0));
+    assert_eq!(f.low(), Some(y));
+    assert_eq!(f.high(), Some(ny));
+    assert_eq!(t.top_var(), None);
+    assert_eq!(fl.low(), None);
+    match f.view() {
+        NodeView::If { var, low, high } => assert_eq!((var, low, high), (0, y, ny)),
+        _ => panic!("expected an internal node")
+    }
+    assert_eq!(t.view(), NodeView::True);
+
+    // Nombre de chemins vers VRAI, et hauteur.
+    assert_eq!(f.fold(|b| b as u64, |_, lo, hi| lo + hi), 2);
+    assert_eq!(f.fold(|_| 0, |_, lo, hi| 1 + lo.max(hi)), 2);
+
+    // Sur la parité de 100 variables, le nombre de chemins (2^99) rend
+    // indispensable le partage des calculs.
+    let mut p = ctx.false_();
+    for v in 0..100u8 {
+        let x = ctx.var(v);
+        p = ctx.xor(p, x)
+    }
+    assert_eq!(p.fold(|b| b as u128, |_, lo, hi| lo + hi), 1 << 99);
+    assert_eq!(p.fold(|_| vec![], |v, lo: &Vec<u8>, _| [&[v][..], lo].concat()).len(), 100);
+}
@@ -0,0 +1,236 @@
+// Calcul de couvertures irredondantes en somme de produits (ISOP), par
+// l'algorithme de Minato et Morreale.
+//
+// Étant donné un intervalle de fonctions booléennes [L, U] (avec L ⇒ U),
+// l'algorithme calcule une fonction f telle que L ⇒ f ⇒ U, ainsi qu'une
+// couverture de f par des cubes (conjonctions de littéraux) irredondante :
+// aucun cube ne peut être retiré, et aucun littéral ne peut être retiré d'un
+// cube, sans sortir de l'intervalle. En prenant L = U = f, on obtient une
+// forme normale disjonctive de f bien plus compacte que l'énumération de tous
+// les chemins du BDD.
+//
+// L'algorithme procède récursivement sur la plus petite variable x de L et U :
+// il calcule d'abord les cubes qui doivent contenir le littéral !x (resp. x),
+// puis ceux qui peuvent se passer de x.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use super::{Bdd, Context, Node};
+use super::zdd::{Zdd, ZddContext};
+
+// Une couverture d'une fonction booléenne par des cubes. Chaque cube est une
+// liste de littéraux `(variable, polarité)`, triée par variable. La
+// couverture vide représente FAUX, et le cube vide représente VRAI.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cover<V>(pub Vec<Vec<(V, bool)>>);
+
+// Une couverture s'affiche comme une formule en forme normale disjonctive,
+// avec les mêmes conventions que l'affichage des BDDs.
+impl<V: fmt::Display> fmt::Display for Cover<V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "false")
+        }
+        for (i, cube) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " | ")?
+            }
+            if cube.is_empty() {
+                write!(f, "true")?
+            }
+            for (j, (var, pos)) in cube.iter().enumerate() {
+                if j > 0 {
+                    write!(f, " & ")?
+                }
+                write!(f, "{}x{}", if *pos { "" } else { "!" }, var)?
+            }
+        }
+        return Ok(())
+    }
+}
+
+// La fonction `top_cofactors` renvoie les cofacteurs de `b` par rapport à
+// `x`, en supposant que `x` est inférieure ou égale à la variable de tête de
+// `b`.
+fn top_cofactors<'arena, V: Copy + Eq>(b: Bdd<'arena, V>, x: V) -> [Bdd<'arena, V>; 2] {
+    match b {
+        Bdd(Node::If { var, children }) if *var == x => *children,
+        _ => [b, b]
+    }
+}
+
+type IsopMemo<'arena, V> =
+    HashMap<(Bdd<'arena, V>, Bdd<'arena, V>), (Vec<Vec<(V, bool)>>, Bdd<'arena, V>)>;
+
+impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
+    fn isop_rec(&mut self, l: Bdd<'arena, V>, u: Bdd<'arena, V>, memo: &mut IsopMemo<'arena, V>)
+                -> (Vec<Vec<(V, bool)>>, Bdd<'arena, V>) {
+        if l == self.false_() {
+            return (Vec::new(), l)
+        }
+        if u == self.true_() {
+            return (vec![Vec::new()], u)
+        }
+        if let Some(res) = memo.get(&(l, u)) {
+            return res.clone()
+        }
+
+        let x = match (l, u) {
+            (Bdd(Node::If { var: a, .. }), Bdd(Node::If { var: b, .. })) => *a.min(b),
+            (Bdd(Node::If { var, .. }), _) | (_, Bdd(Node::If { var, .. })) => *var,
+            _ => unreachable!()
+        };
+        let [l0, l1] = top_cofactors(l, x);
+        let [u0, u1] = top_cofactors(u, x);
+
+        // Cubes contenant !x : ils couvrent la partie de L0 hors de U1.
+        let nu1 = self.not(u1);
+        let l0_only = self.and(l0, nu1);
+        let (c0, f0) = self.isop_rec(l0_only, u0, memo);
+        // Cubes contenant x.
+        let nu0 = self.not(u0);
+        let l1_only = self.and(l1, nu0);
+        let (c1, f1) = self.isop_rec(l1_only, u1, memo);
+        // Cubes indépendants de x, couvrant ce qui reste de L.
+        let nf0 = self.not(f0);
+        let nf1 = self.not(f1);
+        let r0 = self.and(l0, nf0);
+        let r1 = self.and(l1, nf1);
+        let lstar = self.or(r0, r1);
+        let ustar = self.and(u0, u1);
+        let (cstar, fstar) = self.isop_rec(lstar, ustar, memo);
+
+        let mut cubes = Vec::with_capacity(c0.len() + c1.len() + cstar.len());
+        for (cs, pos) in [(c0, false), (c1, true)] {
+            for mut c in cs {
+                c.insert(0, (x, pos));
+                cubes.push(c)
+            }
+        }
+        cubes.extend(cstar);
+        let f01 = self.node(x, [f0, f1]);
+        let f = self.or(f01, fstar);
+
+        memo.insert((l, u), (cubes.clone(), f));
+        return (cubes, f)
+    }
+
+    // La méthode `isop_interval` calcule une couverture irredondante d'une
+    // fonction comprise entre `lower` et `upper`, et renvoie cette couverture
+    // ainsi que le BDD de la fonction couverte. Elle suppose que `lower`
+    // implique `upper`.
+    pub fn isop_interval(&mut self, lower: Bdd<'arena, V>, upper: Bdd<'arena, V>)
+                         -> (Cover<V>, Bdd<'arena, V>) {
+        let nupper = self.not(upper);
+        assert!(self.and(lower, nupper) == self.false_(), "lower must imply upper");
+        let (cubes, f) = self.isop_rec(lower, upper, &mut HashMap::new());
+        return (Cover(cubes), f)
+    }
+
+    // La méthode `isop` calcule une couverture irredondante du BDD `f`.
+    pub fn isop(&mut self, f: Bdd<'arena, V>) -> Cover<V> {
+        return self.isop_interval(f, f).0
+    }
+}
+
+// Une couverture peut aussi être représentée par un ZDD de cubes : chaque
+// cube est l'ensemble de ses littéraux, un littéral étant une variable du ZDD
+// de la forme `(variable, polarité)`. Les cubes partageant des littéraux
+// partagent alors leurs nœuds.
+impl<V: Hash + Copy + Ord> Cover<V> {
+    // La méthode `to_zdd` renvoie le ZDD des cubes de la couverture.
+    pub fn to_zdd<'arena>(&self, z: &mut ZddContext<'arena, (V, bool)>) -> Zdd<'arena, (V, bool)> {
+        let mut res = z.empty();
+        for cube in &self.0 {
+            let c = z.set(cube);
+            res = z.union(res, c)
+        }
+        return res
+    }
+
+    // La fonction `from_zdd` renvoie la couverture formée des cubes du ZDD
+    // `f`, qui ne doivent pas contenir deux littéraux opposés.
+    pub fn from_zdd(f: Zdd<'_, (V, bool)>) -> Self {
+        return Cover(f.iter().collect())
+    }
+}
+
+#[test]
+fn test_isop() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let x = ctx.var(0u16);
+    let y = ctx.var(1);
+    let z = ctx.var(2);
+    let t = ctx.true_();
+    let f = ctx.false_();
+
+    assert_eq!(ctx.isop(t), Cover(vec![vec![]]));
+    assert_eq!(ctx.isop(f), Cover(vec![]));
+    assert_eq!(ctx.isop(x).to_string(), "x0");
+    let nx = ctx.not(x);
+    assert_eq!(ctx.isop(nx).to_string(), "!x0");
+
+    // x | y a 3 chemins vers VRAI, mais une couverture de 2 cubes.
+    let xoy = ctx.or(x, y);
+    assert_eq!(ctx.isop(xoy), Cover(vec![vec![(0, true)], vec![(1, true)]]));
+
+    let xy = ctx.and(x, y);
+    let nxz = ctx.and(nx, z);
+    let g = ctx.or(xy, nxz);
+    let cover = ctx.isop(g);
+    assert_eq!(cover.to_string(), "!x0 & x2 | x0 & x1");
+
+    // Avec des termes de don't care, la couverture peut être plus simple.
+    let lower = xy;
+    let (cover, h) = ctx.isop_interval(lower, xoy);
+    assert_eq!(cover.0.len(), 1);
+    assert_eq!(cover.0[0].len(), 1);
+    assert_eq!(ctx.and(lower, h), lower);
+}
+
+#[test]
+fn test_isop_irredundant() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let vars: Vec<Bdd<u16>> = (0..4).map(|i| ctx.var(i)).collect();
+    let a = ctx.xor(vars[0], vars[1]);
+    let b = ctx.and(vars[2], vars[3]);
+    let f = ctx.or(a, b);
+    let cover = ctx.isop(f);
+
+    // La couverture représente bien `f`.
+    let mut g = ctx.false_();
+    for cube in &cover.0 {
+        let mut c = ctx.true_();
+        for &(v, pos) in cube {
+            let x = ctx.var(v);
+            let l = if pos { x } else { ctx.not(x) };
+            c = ctx.and(c, l)
+        }
+        g = ctx.or(g, c)
+    }
+    assert_eq!(g, f);
+    assert_eq!(cover.0.len(), 3);
+}
+
+#[test]
+fn test_isop_zdd() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let mut z = ZddContext::new(&allo);
+    let vars: Vec<u16> = (0..6).collect();
+    let f = ctx.at_least(3, &vars);
+    let cover = ctx.isop(f);
+    let zf = cover.to_zdd(&mut z);
+    // Les C(6, 3) = 20 cubes de trois littéraux positifs.
+    assert_eq!(zf.count(), 20);
+    let mut back = Cover::from_zdd(zf);
+    let mut expected = cover.clone();
+    back.0.sort();
+    expected.0.sort();
+    assert_eq!(back, expected);
+    assert!(Cover::<u16>(vec![]).to_zdd(&mut z) == z.empty());
+    assert!(Cover::<u16>(vec![vec![]]).to_zdd(&mut z) == z.base());
+}
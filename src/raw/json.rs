@@ -0,0 +1,134 @@
+// Import et export des BDDs au format JSON (fonctionnalité `json`).
+//
+// Un ensemble de BDDs est encodé par un objet de la forme :
+//     {
+//       "nodes": [{"id": 2, "var": 0, "low": 0, "high": 1}, ...],
+//       "roots": [{"name": "f", "node": 2}, ...]
+//     }
+// Les identifiants 0 et 1 désignent les terminaux FAUX et VRAI. Chaque nœud
+// interne apparaît une seule fois dans la liste `nodes`, après ses fils `low`
+// (`children[0]`) et `high` (`children[1]`). Les variables sont encodées par
+// leur sérialisation `serde`.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use serde::{Serialize, de::DeserializeOwned, de::Error};
+use serde_json::{json, Value};
+use super::{Bdd, Context, Node, topological};
+
+impl<'arena, V: Hash + Copy + Ord + Serialize + DeserializeOwned> Context<'arena, V> {
+    // La méthode `to_json` renvoie l'encodage JSON des BDDs de `roots`, avec
+    // leurs noms.
+    pub fn to_json(&self, roots: &[(&str, Bdd<'arena, V>)]) -> Value {
+        let bdds: Vec<Bdd<'arena, V>> = roots.iter().map(|&(_, b)| b).collect();
+        let mut ids: HashMap<Bdd<'arena, V>, usize> = HashMap::new();
+        let id = |ids: &HashMap<Bdd<'arena, V>, usize>, b: Bdd<'arena, V>| match b {
+            Bdd(Node::False) => 0,
+            Bdd(Node::True) => 1,
+            Bdd(Node::If { .. }) => ids[&b],
+        };
+
+        let mut nodes = Vec::new();
+        for (i, b) in topological(&bdds).into_iter().enumerate() {
+            if let Bdd(Node::If { var, children }) = b {
+                nodes.push(json!({
+                    "id": i + 2,
+                    "var": var,
+                    "low": id(&ids, children[0]),
+                    "high": id(&ids, children[1]),
+                }));
+            }
+            ids.insert(b, i + 2);
+        }
+        let roots: Vec<Value> = roots.iter().map(|&(name, b)| json!({
+            "name": name,
+            "node": id(&ids, b),
+        })).collect();
+        return json!({ "nodes": nodes, "roots": roots })
+    }
+
+    // La méthode `from_json` reconstruit dans ce contexte les BDDs encodés
+    // par `to_json`, et renvoie les racines avec leurs noms.
+    pub fn from_json(&mut self, json: &Value)
+                     -> Result<Vec<(String, Bdd<'arena, V>)>, serde_json::Error> {
+        let field = |v: &Value, name: &str| -> Result<Value, serde_json::Error> {
+            return v.get(name).cloned()
+                .ok_or_else(|| serde_json::Error::custom(format!("missing field `{}`", name)))
+        };
+        let id = |v: &Value, name: &str| -> Result<u64, serde_json::Error> {
+            return serde_json::from_value(field(v, name)?)
+        };
+
+        let mut bdds: HashMap<u64, Bdd<'arena, V>> = HashMap::new();
+        bdds.insert(0, self.false_());
+        bdds.insert(1, self.true_());
+        let get = |bdds: &HashMap<u64, Bdd<'arena, V>>, i: u64| {
+            return bdds.get(&i).copied()
+                .ok_or_else(|| serde_json::Error::custom(format!("unknown node {}", i)))
+        };
+
+        let nodes: Vec<Value> = serde_json::from_value(field(json, "nodes")?)?;
+        for n in &nodes {
+            let var: V = serde_json::from_value(field(n, "var")?)?;
+            let lo = get(&bdds, id(n, "low")?)?;
+            let hi = get(&bdds, id(n, "high")?)?;
+            // Les identifiants 0 et 1 sont réservés aux terminaux, et chaque
+            // nœud doit avoir un identifiant propre : sinon, les références
+            // suivantes désigneraient silencieusement un autre BDD.
+            let i = id(n, "id")?;
+            if i < 2 {
+                return Err(serde_json::Error::custom(format!("reserved node id {}", i)))
+            }
+            if bdds.contains_key(&i) {
+                return Err(serde_json::Error::custom(format!("duplicate node id {}", i)))
+            }
+            let b = self.branch(var, [lo, hi]);
+            bdds.insert(i, b);
+        }
+
+        let roots: Vec<Value> = serde_json::from_value(field(json, "roots")?)?;
+        let mut res = Vec::new();
+        for r in &roots {
+            let name: String = serde_json::from_value(field(r, "name")?)?;
+            res.push((name, get(&bdds, id(r, "node")?)?))
+        }
+        return Ok(res)
+    }
+}
+
+#[test]
+fn test_json() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let x = ctx.var(0u16);
+    let y = ctx.var(1);
+    let xy = ctx.and(x, y);
+    let f = ctx.or(xy, x);
+    let t = ctx.true_();
+
+    let json = ctx.to_json(&[("xy", xy), ("t", t)]);
+    assert_eq!(json, json!({
+        "nodes": [{"id": 2, "var": 1, "low": 0, "high": 1},
+                  {"id": 3, "var": 0, "low": 0, "high": 2}],
+        "roots": [{"name": "xy", "node": 3}, {"name": "t", "node": 1}]
+    }));
+    let roots = ctx.from_json(&json).unwrap();
+    assert_eq!(roots, vec![("xy".to_string(), xy), ("t".to_string(), t)]);
+
+    let json = ctx.to_json(&[("f", f)]);
+    let text = json.to_string();
+    let allo2 = bumpalo::Bump::new();
+    let mut ctx2: Context<u16> = Context::new(&allo2);
+    let roots = ctx2.from_json(&serde_json::from_str(&text).unwrap()).unwrap();
+    assert_eq!(roots[0].1.nsat(&[0, 1]), 2);
+
+    assert!(ctx.from_json(&json!({"nodes": [], "roots": [{"name": "f", "node": 7}]})).is_err());
+    assert!(ctx.from_json(&json!({"roots": []})).is_err());
+
+    // Identifiants réservés aux terminaux, ou en double.
+    let node = |i: u64| json!({"id": i, "var": 0, "low": 0, "high": 1});
+    for nodes in [vec![node(0)], vec![node(1)], vec![node(2), node(2)]].iter() {
+        let err = ctx.from_json(&json!({"nodes": nodes, "roots": []}));
+        assert!(err.is_err());
+    }
+}
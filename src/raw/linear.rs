@@ -0,0 +1,154 @@
+// Contraintes pseudo-booléennes linéaires : Σ aᵢ·xᵢ ≤ k, ≥ k ou = k, avec des
+// coefficients entiers.
+//
+// La construction suit l'algorithme d'Abío, Nieuwenhuis, Oliveras et
+// Rodríguez-Carbonell (« BDDs for Pseudo-Boolean Constraints – Revisited »).
+// On se ramène d'abord à des coefficients positifs, en remplaçant aᵢ·xᵢ par
+// aᵢ + |aᵢ|·!xᵢ lorsque aᵢ < 0. Le BDD de Σ_{j ≥ i} aⱼ·lⱼ ≤ K, pour les
+// littéraux à partir du i-ème, est alors le même pour tout un intervalle
+// [β, γ] de valeurs de K. La construction récursive calcule cet intervalle
+// en même temps que le BDD, et le mémorise pour chaque couche i : un appel
+// dont la borne tombe dans un intervalle connu est résolu sans calcul. Le
+// nombre de nœuds créés est ainsi borné par la taille du BDD résultat, qui
+// reste polynomiale dans les cas usuels (petits coefficients, par exemple).
+
+use std::collections::BTreeMap;
+use std::hash::Hash;
+use super::{Bdd, Context};
+
+// Les intervalles connus de chaque couche, indexés par leur borne inférieure
+// et associés à leur borne supérieure et à leur BDD.
+type Layers<'arena, V> = Vec<BTreeMap<i128, (i128, Bdd<'arena, V>)>>;
+
+impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
+    // La méthode privée `linear_rec` renvoie le BDD de
+    // Σ_{j ≥ i} w[j]·l[j] ≤ k et son intervalle [β, γ], où `lits[j]` est
+    // `(w[j], v[j], pos[j])`, avec `w[j] > 0`, et `suffix[j]` est la somme des
+    // `w` à partir de `j`.
+    fn linear_rec(&mut self, lits: &[(i128, V, bool)], suffix: &[i128], i: usize, k: i128,
+                  layers: &mut Layers<'arena, V>) -> (Bdd<'arena, V>, i128, i128) {
+        if k < 0 {
+            return (self.false_(), i128::MIN, -1)
+        }
+        if k >= suffix[i] {
+            return (self.true_(), suffix[i], i128::MAX)
+        }
+        if let Some((&beta, &(gamma, b))) = layers[i].range(..=k).next_back() {
+            if k <= gamma {
+                return (b, beta, gamma)
+            }
+        }
+        let (w, v, pos) = lits[i];
+        // `lo` : le littéral est faux, `hi` : le littéral est vrai.
+        let (lo, beta0, gamma0) = self.linear_rec(lits, suffix, i + 1, k, layers);
+        let (hi, beta1, gamma1) = self.linear_rec(lits, suffix, i + 1, k - w, layers);
+        let b = if pos { self.node(v, [lo, hi]) } else { self.node(v, [hi, lo]) };
+        let beta = beta0.max(beta1.saturating_add(w));
+        let gamma = gamma0.min(gamma1.saturating_add(w));
+        layers[i].insert(beta, (gamma, b));
+        return (b, beta, gamma)
+    }
+
+    // La méthode privée `linear` renvoie le BDD de s·Σ aᵢ·xᵢ ≤ s·`bound`,
+    // pour un signe `s` valant 1 ou -1. Les coefficients sont convertis en
+    // `i128` avant d'être multipliés par `s` : l'opposé de `i64::MIN` ne
+    // déborde donc pas.
+    fn linear(&mut self, terms: &[(i64, V)], bound: i64, s: i128) -> Bdd<'arena, V> {
+        let mut coefs: BTreeMap<V, i128> = BTreeMap::new();
+        for &(a, v) in terms {
+            *coefs.entry(v).or_insert(0) += s * a as i128
+        }
+        let mut k = s * bound as i128;
+        let mut lits = Vec::with_capacity(coefs.len());
+        for (v, a) in coefs {
+            if a > 0 {
+                lits.push((a, v, true))
+            } else if a < 0 {
+                k -= a;
+                lits.push((-a, v, false))
+            }
+        }
+        let mut suffix = vec![0; lits.len() + 1];
+        for i in (0..lits.len()).rev() {
+            suffix[i] = suffix[i + 1] + lits[i].0
+        }
+        let mut layers = vec![BTreeMap::new(); lits.len()];
+        return self.linear_rec(&lits, &suffix, 0, k, &mut layers).0
+    }
+
+    // La méthode `linear_le` renvoie le BDD de Σ aᵢ·xᵢ ≤ `bound`, où `terms`
+    // est la liste des couples `(aᵢ, xᵢ)`. Une variable peut apparaître
+    // plusieurs fois : ses coefficients sont alors additionnés.
+    pub fn linear_le(&mut self, terms: &[(i64, V)], bound: i64) -> Bdd<'arena, V> {
+        return self.linear(terms, bound, 1)
+    }
+
+    // La méthode `linear_ge` renvoie le BDD de Σ aᵢ·xᵢ ≥ `bound`.
+    pub fn linear_ge(&mut self, terms: &[(i64, V)], bound: i64) -> Bdd<'arena, V> {
+        return self.linear(terms, bound, -1)
+    }
+
+    // La méthode `linear_eq` renvoie le BDD de Σ aᵢ·xᵢ = `bound`.
+    pub fn linear_eq(&mut self, terms: &[(i64, V)], bound: i64) -> Bdd<'arena, V> {
+        let le = self.linear_le(terms, bound);
+        let ge = self.linear_ge(terms, bound);
+        return self.and(le, ge)
+    }
+}
+
+#[test]
+fn test_linear() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let vars = [0u8, 1, 2, 3, 4];
+    let terms = [(3, 0), (-2, 1), (5, 2), (1, 3), (-4, 4), (2, 1)];
+
+    // Comparaison avec l'évaluation directe de la somme, pour chaque
+    // affectation.
+    let sum = |r: usize| -> i64 {
+        terms.iter().map(|&(a, v)| if r >> (4 - v) & 1 == 1 { a } else { 0 }).sum()
+    };
+    for bound in -6..=11 {
+        let le = ctx.linear_le(&terms, bound).to_truth_table(&vars);
+        let ge = ctx.linear_ge(&terms, bound).to_truth_table(&vars);
+        let eq = ctx.linear_eq(&terms, bound).to_truth_table(&vars);
+        for r in 0..32 {
+            assert_eq!(le[r], sum(r) <= bound);
+            assert_eq!(ge[r], sum(r) >= bound);
+            assert_eq!(eq[r], sum(r) == bound);
+        }
+    }
+    assert_eq!(ctx.linear_le(&[], 0), ctx.true_());
+    assert_eq!(ctx.linear_le(&[(1, 0)], -1), ctx.false_());
+    assert_eq!(ctx.linear_ge(&[(1, 0), (1, 1)], 1), ctx.at_least(1, &[0, 1]));
+}
+
+#[test]
+fn test_linear_size() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    // Avec des coefficients 1, 2, ..., n, la somme est bornée par n², et
+    // chaque couche compte au plus n² nœuds.
+    let terms: Vec<(i64, u16)> = (0..40).map(|i| (i as i64 + 1, i)).collect();
+    let f = ctx.linear_le(&terms, 400);
+    assert!(super::topological(&[f]).len() <= 40 * 40 * 40);
+    let all: Vec<u16> = (0..40).collect();
+    assert_eq!(ctx.linear_le(&terms, 820), ctx.true_());
+    assert_eq!(ctx.linear_ge(&terms, 820), ctx.at_least(40, &all));
+}
+
+#[test]
+fn test_linear_extreme() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let x = ctx.var(0u8);
+    let nx = ctx.not(x);
+    let (t, f) = (ctx.true_(), ctx.false_());
+    assert_eq!(ctx.linear_ge(&[(i64::MIN, 0)], 0), nx);
+    assert_eq!(ctx.linear_le(&[(i64::MIN, 0)], i64::MIN), x);
+    assert_eq!(ctx.linear_ge(&[(1, 0)], i64::MIN), t);
+    assert_eq!(ctx.linear_ge(&[(i64::MAX, 0)], i64::MAX), x);
+    assert_eq!(ctx.linear_eq(&[(i64::MIN, 0)], i64::MIN), x);
+    assert_eq!(ctx.linear_eq(&[(i64::MIN, 0), (i64::MIN, 0)], 0), nx);
+    assert_eq!(ctx.linear_le(&[(i64::MAX, 0), (i64::MAX, 1)], i64::MIN), f);
+}
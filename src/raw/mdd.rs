@@ -0,0 +1,390 @@
+// Diagrammes de décision multivalués (MDDs).
+//
+// Un MDD généralise un BDD à des variables de domaine fini {0, ..., d - 1},
+// avec d ≥ 2 : chaque nœud interne teste une variable et a un fils par valeur
+// de celle-ci. Les règles de réduction sont celles des BDDs : un nœud dont
+// tous les fils sont égaux est supprimé, et les nœuds sont partagés par
+// hash-consing dans un contexte `MddContext`, distinct du contexte des BDDs.
+// La taille du domaine de chaque variable est déclarée dans le contexte
+// avant utilisation, par `add_var`.
+//
+// Les fils d'un nœud sont alloués dans l'arène sous forme de tranche. La
+// table de hash-consing associe donc la variable et la liste des fils d'un
+// nœud au MDD correspondant, plutôt que de contenir les nœuds eux-mêmes.
+
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use bumpalo::Bump;
+use super::{Bdd, Context};
+
+enum MNode<'arena, V> {
+    False, True,
+    If { var: V, children: &'arena [Mdd<'arena, V>] }
+}
+
+pub struct Mdd<'arena, V>(&'arena MNode<'arena, V>);
+
+handle!(Mdd<V>);
+
+// Les opérations binaires mémoïsées dans le contexte.
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+enum MOp { And, Or, Xor }
+
+impl MOp {
+    fn eval(self, x: bool, y: bool) -> bool {
+        match self {
+            MOp::And => x && y,
+            MOp::Or => x || y,
+            MOp::Xor => x ^ y,
+        }
+    }
+}
+
+type MKey<'arena, V> = (MOp, Mdd<'arena, V>, Mdd<'arena, V>);
+
+pub struct MddContext<'arena, V> {
+    alloc: &'arena Bump,
+    false_: Mdd<'arena, V>,
+    true_: Mdd<'arena, V>,
+
+    // La taille du domaine de chaque variable déclarée.
+    domains: BTreeMap<V, usize>,
+
+    // La table de hash-consing des nœuds internes. Contrairement aux MTBDDs
+    // et aux ZDDs, elle n'utilise pas `Unique` : les fils d'un nœud forment
+    // une tranche de longueur variable, qui ne peut être recherchée dans une
+    // table de nœuds qu'une fois allouée dans l'arène. Les clés sont donc des
+    // copies des fils, et seuls les nœuds nouveaux sont alloués.
+    unique: HashMap<(V, Vec<Mdd<'arena, V>>), Mdd<'arena, V>>,
+
+    // Les opérations étant commutatives, les clés sont ordonnées par adresse.
+    memo: HashMap<MKey<'arena, V>, Mdd<'arena, V>>,
+}
+
+impl<'arena, V: Hash + Copy + Ord> MddContext<'arena, V> {
+    pub fn new(alloc: &'arena Bump) -> Self {
+        return MddContext { alloc: alloc,
+                            false_: Mdd(alloc.alloc(MNode::False)), true_: Mdd(alloc.alloc(MNode::True)),
+                            domains: BTreeMap::new(), unique: HashMap::new(), memo: HashMap::new() }
+    }
+
+    // La méthode `add_var` déclare la variable `v`, de domaine
+    // {0, ..., size - 1}. Une variable peut être redéclarée avec la même
+    // taille, mais pas avec une autre : les nœuds existants qui la testent
+    // ont un fils par valeur de l'ancien domaine.
+    pub fn add_var(&mut self, v: V, size: usize) {
+        assert!(size >= 2, "domain of size less than 2");
+        let old = *self.domains.entry(v).or_insert(size);
+        assert!(old == size, "MDD variable redeclared with a different domain");
+    }
+
+    // La méthode `domain` renvoie la taille du domaine de la variable `v`,
+    // qui doit avoir été déclarée.
+    pub fn domain(&self, v: V) -> usize {
+        return *self.domains.get(&v).expect("undeclared MDD variable")
+    }
+
+    pub fn true_(&self) -> Mdd<'arena, V> {
+        return self.true_
+    }
+
+    pub fn false_(&self) -> Mdd<'arena, V> {
+        return self.false_
+    }
+
+    // La méthode privée `node` crée un nœud interne, en supposant que `var`
+    // est strictement plus petite que les variables de `children`.
+    fn node(&mut self, var: V, children: Vec<Mdd<'arena, V>>) -> Mdd<'arena, V> {
+        if children.iter().all(|&c| c == children[0]) {
+            return children[0]
+        }
+        let key = (var, children);
+        if let Some(&res) = self.unique.get(&key) {
+            return res
+        }
+        let slice = self.alloc.alloc_slice_copy(&key.1);
+        let res = Mdd(self.alloc.alloc(MNode::If { var: var, children: slice }));
+        self.unique.insert(key, res);
+        return res
+    }
+
+    // La méthode `literal_in` renvoie le MDD vrai lorsque la variable `v`
+    // prend une valeur de `values`.
+    pub fn literal_in(&mut self, v: V, values: &[usize]) -> Mdd<'arena, V> {
+        let d = self.domain(v);
+        let children = (0..d).map(|k| if values.contains(&k) { self.true_ } else { self.false_ }).collect();
+        return self.node(v, children)
+    }
+
+    // La méthode `literal` renvoie le MDD vrai lorsque `v` vaut `value`.
+    pub fn literal(&mut self, v: V, value: usize) -> Mdd<'arena, V> {
+        assert!(value < self.domain(v), "value out of the variable domain");
+        return self.literal_in(v, &[value])
+    }
+
+    // La méthode privée `children` renvoie les fils de `f` pour la variable
+    // `var`, de domaine `d`, en supposant que `var` est au plus la variable
+    // de la racine de `f`.
+    fn children(f: Mdd<'arena, V>, var: V, d: usize) -> Vec<Mdd<'arena, V>> {
+        match f.0 {
+            MNode::If { var: v, children } if *v == var => children.to_vec(),
+            _ => vec![f; d]
+        }
+    }
+
+    fn apply(&mut self, op: MOp, a: Mdd<'arena, V>, b: Mdd<'arena, V>) -> Mdd<'arena, V> {
+        match (op, a.0, b.0) {
+            (_, MNode::If { .. }, _) | (_, _, MNode::If { .. }) => (),
+            (_, x, y) => {
+                let value = |n: &MNode<V>| matches!(n, MNode::True);
+                return if op.eval(value(x), value(y)) { self.true_ } else { self.false_ }
+            }
+        }
+        match (op, a.0, b.0) {
+            (MOp::And, MNode::False, _) | (MOp::Or, MNode::True, _) => return a,
+            (MOp::And, _, MNode::False) | (MOp::Or, _, MNode::True) => return b,
+            (MOp::And, MNode::True, _) | (MOp::Or, MNode::False, _) | (MOp::Xor, MNode::False, _) => return b,
+            (MOp::And, _, MNode::True) | (MOp::Or, _, MNode::False) | (MOp::Xor, _, MNode::False) => return a,
+            _ => ()
+        }
+        let key = if (a.0 as *const MNode<V>) < (b.0 as *const MNode<V>) { (op, a, b) } else { (op, b, a) };
+        if let Some(&res) = self.memo.get(&key) {
+            return res
+        }
+        let var = match (a.0, b.0) {
+            (MNode::If { var: va, .. }, MNode::If { var: vb, .. }) => *va.min(vb),
+            (MNode::If { var, .. }, _) | (_, MNode::If { var, .. }) => *var,
+            _ => unreachable!()
+        };
+        let d = self.domain(var);
+        let ca = Self::children(a, var, d);
+        let cb = Self::children(b, var, d);
+        let children = ca.into_iter().zip(cb).map(|(x, y)| self.apply(op, x, y)).collect();
+        let res = self.node(var, children);
+        self.memo.insert(key, res);
+        return res
+    }
+
+    pub fn and(&mut self, a: Mdd<'arena, V>, b: Mdd<'arena, V>) -> Mdd<'arena, V> {
+        return self.apply(MOp::And, a, b)
+    }
+
+    pub fn or(&mut self, a: Mdd<'arena, V>, b: Mdd<'arena, V>) -> Mdd<'arena, V> {
+        return self.apply(MOp::Or, a, b)
+    }
+
+    pub fn xor(&mut self, a: Mdd<'arena, V>, b: Mdd<'arena, V>) -> Mdd<'arena, V> {
+        return self.apply(MOp::Xor, a, b)
+    }
+
+    // La négation est calculée comme le ou exclusif avec VRAI.
+    pub fn not(&mut self, a: Mdd<'arena, V>) -> Mdd<'arena, V> {
+        let t = self.true_;
+        return self.apply(MOp::Xor, a, t)
+    }
+
+    // La méthode `count` renvoie le nombre d'affectations des variables
+    // déclarées qui satisfont `f`.
+    pub fn count(&self, f: Mdd<'arena, V>) -> u64 {
+        let vars: Vec<(V, usize)> = self.domains.iter().map(|(&v, &d)| (v, d)).collect();
+        // `suffix[i]` est le nombre d'affectations des variables à partir de
+        // la i-ème.
+        let mut suffix = vec![1u64; vars.len() + 1];
+        for i in (0..vars.len()).rev() {
+            suffix[i] = suffix[i + 1] * vars[i].1 as u64
+        }
+        let index = |f: Mdd<'arena, V>| match f.0 {
+            MNode::If { var, .. } => vars.binary_search_by(|(v, _)| v.cmp(var)).expect("undeclared MDD variable"),
+            _ => vars.len()
+        };
+        // `count_rec(f)` compte les affectations des variables à partir de
+        // celle de la racine de `f`.
+        fn count_rec<'arena, V, F>(f: Mdd<'arena, V>, index: &F, suffix: &[u64],
+                                   memo: &mut HashMap<Mdd<'arena, V>, u64>) -> u64
+          where F: Fn(Mdd<'arena, V>) -> usize {
+            match f.0 {
+                MNode::False => return 0,
+                MNode::True => return 1,
+                MNode::If { children, .. } => {
+                    if let Some(&n) = memo.get(&f) {
+                        return n
+                    }
+                    let i = index(f);
+                    let n = children.iter().map(|&c| {
+                        count_rec(c, index, suffix, memo) * (suffix[i + 1] / suffix[index(c)])
+                    }).sum();
+                    memo.insert(f, n);
+                    return n
+                }
+            }
+        }
+        return count_rec(f, &index, &suffix, &mut HashMap::new()) * (suffix[0] / suffix[index(f)])
+    }
+
+    // La méthode `to_bdd` convertit `f` en BDD par encodage logarithmique :
+    // une variable `v` de domaine `d` est encodée par les ⌈log₂ d⌉ variables
+    // booléennes `bit(v, j)`, le bit `j` étant de poids 2^j. Les codes hors du
+    // domaine rendent le BDD faux, y compris pour les variables déclarées que
+    // `f` ne teste pas : le BDD a ainsi autant de modèles (sur les bits de
+    // toutes les variables déclarées) que `f` d'affectations.
+    pub fn to_bdd<'b, W, F>(&self, ctx: &mut Context<'b, W>, f: Mdd<'arena, V>, bit: F) -> Bdd<'b, W>
+      where W: Hash + Copy + Ord, F: Fn(V, usize) -> W {
+        let mut memo = HashMap::new();
+        let mut res = self.to_bdd_rec(ctx, f, &bit, &mut memo);
+        for (&v, &d) in &self.domains {
+            let valid = self.valid_codes(ctx, v, d, &bit);
+            res = ctx.and(res, valid)
+        }
+        return res
+    }
+
+    // La méthode privée `valid_codes` renvoie le BDD vrai lorsque le code de
+    // la variable `v`, de domaine `d`, est strictement inférieur à `d`. Les
+    // bits sont comparés à ceux de `d` du poids faible au poids fort : le BDD
+    // `lt` est vrai lorsque les bits déjà parcourus forment un nombre
+    // inférieur à celui formé par les bits correspondants de `d`.
+    fn valid_codes<'b, W, F>(&self, ctx: &mut Context<'b, W>, v: V, d: usize, bit: &F) -> Bdd<'b, W>
+      where W: Hash + Copy + Ord, F: Fn(V, usize) -> W {
+        let width = usize::BITS - (d - 1).leading_zeros();
+        if d == 1 << width {
+            // Tous les codes de `width` bits sont valides.
+            return ctx.true_()
+        }
+        let mut lt = ctx.false_();
+        for j in 0..width as usize {
+            let b = ctx.var(bit(v, j));
+            let nb = ctx.not(b);
+            lt = if d >> j & 1 == 1 { ctx.or(nb, lt) } else { ctx.and(nb, lt) }
+        }
+        return lt
+    }
+
+    fn to_bdd_rec<'b, W, F>(&self, ctx: &mut Context<'b, W>, f: Mdd<'arena, V>, bit: &F,
+                            memo: &mut HashMap<Mdd<'arena, V>, Bdd<'b, W>>) -> Bdd<'b, W>
+      where W: Hash + Copy + Ord, F: Fn(V, usize) -> W {
+        match f.0 {
+            MNode::False => return ctx.false_(),
+            MNode::True => return ctx.true_(),
+            MNode::If { var, children } => {
+                if let Some(&res) = memo.get(&f) {
+                    return res
+                }
+                let d = children.len();
+                let width = usize::BITS - (d - 1).leading_zeros();
+                let bits: Vec<Bdd<'b, W>> = (0..width as usize).map(|j| ctx.var(bit(*var, j))).collect();
+                let mut res = ctx.false_();
+                for (k, &c) in children.iter().enumerate() {
+                    let mut code = self.to_bdd_rec(ctx, c, bit, memo);
+                    for (j, &b) in bits.iter().enumerate() {
+                        let lit = if k >> j & 1 == 1 { b } else { ctx.not(b) };
+                        code = ctx.and(code, lit)
+                    }
+                    res = ctx.or(res, code)
+                }
+                memo.insert(f, res);
+                return res
+            }
+        }
+    }
+}
+
+impl<'arena, V: Copy> Mdd<'arena, V> {
+    // La méthode `eval` calcule la valeur de `self` pour l'affectation qui
+    // donne à chaque variable `v` la valeur `value(v)`.
+    pub fn eval<F: Fn(V) -> usize>(self, value: F) -> bool {
+        let mut f = self;
+        loop {
+            match f.0 {
+                MNode::False => return false,
+                MNode::True => return true,
+                MNode::If { var, children } => f = children[value(*var)]
+            }
+        }
+    }
+}
+
+#[test]
+fn test_mdd() {
+    let allo = Bump::new();
+    let mut m = MddContext::new(&allo);
+    // Trois variables : x ∈ {0, 1, 2}, y ∈ {0, ..., 4}, z ∈ {0, 1}.
+    let sizes = [3, 5, 2];
+    for (v, &d) in sizes.iter().enumerate() {
+        m.add_var(v as u8, d)
+    }
+    // f = (x ≠ y) ∧ (z = 1 ∨ y ∈ {0, 4}).
+    let mut ne = m.false_();
+    for k in 0..3 {
+        let x = m.literal(0, k);
+        let not_y = m.literal_in(1, &[0, 1, 2, 3, 4].iter().copied().filter(|&j| j != k).collect::<Vec<_>>());
+        let both = m.and(x, not_y);
+        ne = m.or(ne, both)
+    }
+    let z = m.literal(2, 1);
+    let y04 = m.literal_in(1, &[0, 4]);
+    let right = m.or(z, y04);
+    let f = m.and(ne, right);
+
+    let mut expected = 0;
+    for x in 0..3 {
+        for y in 0..5 {
+            for zv in 0..2 {
+                let value = |v: u8| [x, y, zv][v as usize];
+                let e = x != y && (zv == 1 || y == 0 || y == 4);
+                assert_eq!(f.eval(value), e);
+                assert_eq!(m.not(f).eval(value), !e);
+                let g = m.xor(f, z);
+                assert_eq!(g.eval(value), e ^ (zv == 1));
+                expected += e as u64;
+            }
+        }
+    }
+    assert_eq!(m.count(f), expected);
+    assert_eq!(m.count(z), 15);
+    assert_eq!(m.count(m.true_()), 30);
+
+    // Réduction : une variable qui peut prendre toutes ses valeurs disparaît.
+    assert!(m.literal_in(1, &[0, 1, 2, 3, 4]) == m.true_());
+    let nz = m.literal(2, 0);
+    assert!(m.or(z, nz) == m.true_());
+
+    // L'encodage logarithmique préserve le nombre de solutions : x et y sont
+    // encodées sur 2 et 3 bits, z sur 1.
+    let mut ctx = Context::new(&allo);
+    let b = m.to_bdd(&mut ctx, f, |v, j| v * 3 + j as u8);
+    let bvars = [0, 1, 3, 4, 5, 6];
+    assert_eq!(b.nsat(&bvars), expected);
+}
+
+#[test]
+fn test_mdd_to_bdd_domains() {
+    // Les variables que le MDD ne teste pas gardent leur contrainte de
+    // domaine dans le BDD.
+    let allo = Bump::new();
+    let mut m = MddContext::new(&allo);
+    m.add_var(0u8, 3);
+    m.add_var(1, 2);
+    m.add_var(2, 5);
+    let allo2 = Bump::new();
+    let mut ctx = Context::new(&allo2);
+    let bit = |v: u8, j: usize| v * 8 + j as u8;
+    let bits = [0, 1, 8, 16, 17, 18];
+    let t = m.true_();
+    let lit = m.literal_in(1, &[1]);
+    let lit0 = m.literal_in(0, &[0, 2]);
+    for &f in [t, lit, lit0].iter() {
+        assert_eq!(m.to_bdd(&mut ctx, f, bit).nsat(&bits), m.count(f));
+    }
+    assert_eq!(m.count(t), 30);
+}
+
+#[test]
+#[should_panic(expected = "MDD variable redeclared with a different domain")]
+fn test_mdd_redeclare() {
+    let allo = Bump::new();
+    let mut m = MddContext::new(&allo);
+    m.add_var(0u8, 3);
+    m.add_var(0, 3);
+    m.literal_in(0, &[1]);
+    m.add_var(0, 4);
+}
@@ -0,0 +1,395 @@
+// Diagrammes de décision à terminaux multiples (MTBDDs, ou ADDs pour
+// « algebraic decision diagrams »).
+//
+// Un MTBDD généralise un BDD en remplaçant les deux terminaux `True` et
+// `False` par des valeurs quelconques : il représente une fonction des
+// variables booléennes vers un ensemble de valeurs, par exemple une fonction
+// de coût ou une distribution de probabilités. Les MTBDDs reprennent la
+// conception des BDDs de ce module : les nœuds sont alloués dans une arène et
+// partagés par hash-consing, dans un contexte `MtbddContext` distinct du
+// contexte des BDDs. Les terminaux sont eux aussi alloués dans l'arène, de
+// sorte que deux MTBDDs sont égaux si et seulement si ils ont la même adresse
+// (voir le module `unique`, commun aux MTBDDs, aux ZDDs et aux MDDs).
+//
+// Les opérations point à point (+, *, min, max) sont calculées par un
+// parcours simultané des deux opérandes, à la manière de `and`, et mémoïsées
+// dans le contexte.
+
+use std::collections::hash_map::HashMap;
+use std::hash::{Hash, Hasher};
+use std::ops::{Add, Mul};
+use bumpalo::Bump;
+use super::{Bdd, Context, Node};
+use super::unique::Unique;
+
+// Le trait `Value` regroupe les contraintes sur les valeurs des terminaux :
+// `Hash` et `Eq` pour le hash-consing, `Ord` pour `min`, `max` et les seuils,
+// et l'addition et la multiplication. Il est implémenté pour tous les types
+// qui vérifient ces contraintes, comme les entiers ou les rationnels.
+pub trait Value: Copy + Hash + Ord + Add<Output = Self> + Mul<Output = Self> { }
+impl<T: Copy + Hash + Ord + Add<Output = T> + Mul<Output = T>> Value for T { }
+
+// Les flottants ne peuvent pas être utilisés directement comme terminaux : ils
+// n'implémentent ni `Hash` ni `Ord`, et les erreurs d'arrondi empêcheraient le
+// partage de terminaux égaux aux arrondis près. Le type `Float` les compare
+// après avoir arrondi leur mantisse à `Float::PRECISION` bits : deux flottants
+// sont égaux s'ils ont le même arrondi, ce qui revient à une tolérance
+// relative d'environ 2^-PRECISION, quel que soit leur ordre de grandeur.
+// `0.0` et `-0.0` sont égaux, et les comparaisons paniquent sur `NaN`, qui
+// n'est égal à aucune valeur.
+#[derive(Copy, Clone, Debug)]
+pub struct Float(pub f64);
+
+impl Float {
+    pub const PRECISION: u32 = 32;
+
+    // La méthode privée `key` renvoie un entier qui identifie l'arrondi de
+    // `self`, et qui croît avec lui. Pour les flottants positifs, l'ordre des
+    // représentations binaires est celui des valeurs : on arrondit donc au
+    // plus proche la représentation de |x| en supprimant les bits de mantisse
+    // en trop (une retenue passe dans l'exposant), puis on rétablit le signe.
+    fn key(self) -> i64 {
+        assert!(!self.0.is_nan(), "NaN value in an MTBDD");
+        let drop = 52 - Float::PRECISION;
+        let abs = self.0.abs().to_bits();
+        let key = ((abs + (1 << (drop - 1))) >> drop) as i64;
+        return if self.0 < 0. { -key } else { key }
+    }
+}
+
+impl PartialEq for Float {
+    fn eq(&self, x: &Float) -> bool {
+        return self.key() == x.key()
+    }
+}
+impl Eq for Float { }
+impl Hash for Float {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state)
+    }
+}
+impl PartialOrd for Float {
+    fn partial_cmp(&self, x: &Float) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(x))
+    }
+}
+impl Ord for Float {
+    fn cmp(&self, x: &Float) -> std::cmp::Ordering {
+        return self.key().cmp(&x.key())
+    }
+}
+impl Add for Float {
+    type Output = Float;
+    fn add(self, x: Float) -> Float {
+        return Float(self.0 + x.0)
+    }
+}
+impl Mul for Float {
+    type Output = Float;
+    fn mul(self, x: Float) -> Float {
+        return Float(self.0 * x.0)
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+enum MtNode<'arena, V, T> {
+    Leaf(T),
+    If { var: V, children: [Mtbdd<'arena, V, T>; 2] }
+}
+
+pub struct Mtbdd<'arena, V, T>(&'arena MtNode<'arena, V, T>);
+
+handle!(Mtbdd<V, T>);
+
+// Les opérations point à point mémoïsées dans le contexte.
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+enum Op { Add, Mul, Min, Max }
+
+impl Op {
+    fn eval<T: Value>(self, x: T, y: T) -> T {
+        match self {
+            Op::Add => x + y,
+            Op::Mul => x * y,
+            Op::Min => x.min(y),
+            Op::Max => x.max(y),
+        }
+    }
+}
+
+type ApplyKey<'arena, V, T> = (Op, Mtbdd<'arena, V, T>, Mtbdd<'arena, V, T>);
+
+pub struct MtbddContext<'arena, V, T> {
+    hashcons: Unique<'arena, MtNode<'arena, V, T>>,
+
+    // Les opérations étant commutatives, les clés sont ordonnées par adresse,
+    // comme pour `and_memo`.
+    apply_memo: HashMap<ApplyKey<'arena, V, T>, Mtbdd<'arena, V, T>>,
+}
+
+impl<'arena, V: Hash + Copy + Ord, T: Value> MtbddContext<'arena, V, T> {
+    pub fn new(alloc: &'arena Bump) -> Self {
+        return MtbddContext { hashcons: Unique::new(alloc), apply_memo: HashMap::new() }
+    }
+
+    fn hashcons(&mut self, n: MtNode<'arena, V, T>) -> Mtbdd<'arena, V, T> {
+        return Mtbdd(self.hashcons.get(n))
+    }
+
+    // La méthode privée `node` crée un nœud interne, en supposant que `var`
+    // est strictement plus petite que les variables de `children`.
+    fn node(&mut self, var: V, children: [Mtbdd<'arena, V, T>; 2]) -> Mtbdd<'arena, V, T> {
+        if children[0] == children[1] {
+            return children[0]
+        }
+        return self.hashcons(MtNode::If { var: var, children: children })
+    }
+
+    // La méthode `constant` renvoie la fonction constante égale à `x`.
+    pub fn constant(&mut self, x: T) -> Mtbdd<'arena, V, T> {
+        return self.hashcons(MtNode::Leaf(x))
+    }
+
+    // La méthode `var` renvoie la fonction valant `hi` si la variable `v` est
+    // vraie, et `lo` sinon.
+    pub fn var(&mut self, v: V, lo: T, hi: T) -> Mtbdd<'arena, V, T> {
+        let children = [self.constant(lo), self.constant(hi)];
+        return self.node(v, children)
+    }
+
+    // La méthode `from_bdd` convertit le BDD `b` en la fonction valant `hi`
+    // là où `b` est vrai, et `lo` ailleurs.
+    pub fn from_bdd(&mut self, b: Bdd<'arena, V>, lo: T, hi: T) -> Mtbdd<'arena, V, T> {
+        let mut memo = HashMap::new();
+        return self.convert_rec(b, lo, hi, &mut memo)
+    }
+
+    // La méthode privée `convert_rec` effectue la conversion de `from_bdd`,
+    // mémoïsée dans `memo`.
+    fn convert_rec(&mut self, b: Bdd<'arena, V>, lo: T, hi: T,
+                    memo: &mut HashMap<Bdd<'arena, V>, Mtbdd<'arena, V, T>>) -> Mtbdd<'arena, V, T> {
+        match b.0 {
+            Node::False => return self.constant(lo),
+            Node::True => return self.constant(hi),
+            Node::If { var, children } => {
+                if let Some(&res) = memo.get(&b) {
+                    return res
+                }
+                let l = self.convert_rec(children[0], lo, hi, memo);
+                let h = self.convert_rec(children[1], lo, hi, memo);
+                let res = self.node(*var, [l, h]);
+                memo.insert(b, res);
+                return res
+            }
+        }
+    }
+
+    // La méthode privée `apply` combine point à point `a` et `b` par `op`.
+    fn apply(&mut self, op: Op, a: Mtbdd<'arena, V, T>, b: Mtbdd<'arena, V, T>) -> Mtbdd<'arena, V, T> {
+        if let (MtNode::Leaf(x), MtNode::Leaf(y)) = (a.0, b.0) {
+            return self.constant(op.eval(*x, *y))
+        }
+        let key = if (a.0 as *const MtNode<V, T>) < (b.0 as *const MtNode<V, T>) { (op, a, b) } else { (op, b, a) };
+        if let Some(&res) = self.apply_memo.get(&key) {
+            return res
+        }
+        let var = match (a.0, b.0) {
+            (MtNode::If { var: va, .. }, MtNode::If { var: vb, .. }) => *va.min(vb),
+            (MtNode::If { var, .. }, _) | (_, MtNode::If { var, .. }) => *var,
+            _ => unreachable!()
+        };
+        let (a0, a1) = cofactors(a, var);
+        let (b0, b1) = cofactors(b, var);
+        let lo = self.apply(op, a0, b0);
+        let hi = self.apply(op, a1, b1);
+        let res = self.node(var, [lo, hi]);
+        self.apply_memo.insert(key, res);
+        return res
+    }
+
+    pub fn add(&mut self, a: Mtbdd<'arena, V, T>, b: Mtbdd<'arena, V, T>) -> Mtbdd<'arena, V, T> {
+        return self.apply(Op::Add, a, b)
+    }
+
+    pub fn mul(&mut self, a: Mtbdd<'arena, V, T>, b: Mtbdd<'arena, V, T>) -> Mtbdd<'arena, V, T> {
+        return self.apply(Op::Mul, a, b)
+    }
+
+    pub fn min(&mut self, a: Mtbdd<'arena, V, T>, b: Mtbdd<'arena, V, T>) -> Mtbdd<'arena, V, T> {
+        return self.apply(Op::Min, a, b)
+    }
+
+    pub fn max(&mut self, a: Mtbdd<'arena, V, T>, b: Mtbdd<'arena, V, T>) -> Mtbdd<'arena, V, T> {
+        return self.apply(Op::Max, a, b)
+    }
+
+    // La méthode `threshold` renvoie le BDD, dans le contexte `ctx`, vrai là
+    // où `f` vaut au moins `k`.
+    pub fn threshold(&mut self, ctx: &mut Context<'arena, V>, f: Mtbdd<'arena, V, T>, k: T) -> Bdd<'arena, V> {
+        let mut memo = HashMap::new();
+        return threshold_rec(ctx, f, k, &mut memo)
+    }
+
+    // La méthode privée `restrict` renvoie le cofacteur de `f` pour la
+    // valeur `value` de la variable `v`.
+    fn restrict(&mut self, f: Mtbdd<'arena, V, T>, v: V, value: bool,
+                memo: &mut HashMap<Mtbdd<'arena, V, T>, Mtbdd<'arena, V, T>>) -> Mtbdd<'arena, V, T> {
+        match f.0 {
+            MtNode::If { var, children } if *var <= v => {
+                if *var == v {
+                    return children[value as usize]
+                }
+                if let Some(&res) = memo.get(&f) {
+                    return res
+                }
+                let lo = self.restrict(children[0], v, value, memo);
+                let hi = self.restrict(children[1], v, value, memo);
+                let res = self.node(*var, [lo, hi]);
+                memo.insert(f, res);
+                return res
+            }
+            _ => return f
+        }
+    }
+
+    // La méthode `sum_abstract` somme `f` sur toutes les valeurs des
+    // variables de `vars` : pour une variable x, Σₓ f = f[x ← 0] + f[x ← 1].
+    // Une variable dont `f` ne dépend pas double donc la fonction.
+    pub fn sum_abstract(&mut self, mut f: Mtbdd<'arena, V, T>, vars: &[V]) -> Mtbdd<'arena, V, T> {
+        let mut vars = vars.to_vec();
+        vars.sort();
+        vars.dedup();
+        for v in vars {
+            let lo = self.restrict(f, v, false, &mut HashMap::new());
+            let hi = self.restrict(f, v, true, &mut HashMap::new());
+            f = self.add(lo, hi)
+        }
+        return f
+    }
+}
+
+// La fonction `cofactors` renvoie les deux fils de `f` si sa racine teste
+// `var`, et `(f, f)` sinon.
+fn cofactors<'arena, V: Copy + Eq, T>(f: Mtbdd<'arena, V, T>, var: V)
+                                      -> (Mtbdd<'arena, V, T>, Mtbdd<'arena, V, T>) {
+    match f.0 {
+        MtNode::If { var: v, children } if *v == var => (children[0], children[1]),
+        _ => (f, f)
+    }
+}
+
+fn threshold_rec<'arena, V: Hash + Copy + Ord, T: Value>(
+    ctx: &mut Context<'arena, V>, f: Mtbdd<'arena, V, T>, k: T,
+    memo: &mut HashMap<Mtbdd<'arena, V, T>, Bdd<'arena, V>>) -> Bdd<'arena, V> {
+    match f.0 {
+        MtNode::Leaf(x) => return if *x >= k { ctx.true_() } else { ctx.false_() },
+        MtNode::If { var, children } => {
+            if let Some(&res) = memo.get(&f) {
+                return res
+            }
+            let lo = threshold_rec(ctx, children[0], k, memo);
+            let hi = threshold_rec(ctx, children[1], k, memo);
+            let res = ctx.node(*var, [lo, hi]);
+            memo.insert(f, res);
+            return res
+        }
+    }
+}
+
+impl<'arena, V: Copy + Ord, T: Copy> Mtbdd<'arena, V, T> {
+    // La méthode `eval` calcule la valeur de la fonction pour l'affectation
+    // qui donne à chaque variable `v` la valeur `value(v)`.
+    pub fn eval<F: Fn(V) -> bool>(self, value: F) -> T {
+        let mut f = self;
+        loop {
+            match f.0 {
+                MtNode::Leaf(x) => return *x,
+                MtNode::If { var, children } => f = children[value(*var) as usize]
+            }
+        }
+    }
+
+    // La méthode `leaf` renvoie la valeur de `self` s'il est constant.
+    pub fn leaf(self) -> Option<T> {
+        match self.0 {
+            MtNode::Leaf(x) => Some(*x),
+            MtNode::If { .. } => None
+        }
+    }
+}
+
+#[test]
+fn test_mtbdd() {
+    let allo = Bump::new();
+    let mut ctx = Context::new(&allo);
+    let mut mt: MtbddContext<u8, i64> = MtbddContext::new(&allo);
+
+    // Le coût 3·x0 + 5·x1 - 2·x2, et le produit (1 + x0)·(1 + x2).
+    let a = mt.var(0, 0, 3);
+    let b = mt.var(1, 0, 5);
+    let c = mt.var(2, 0, -2);
+    let ab = mt.add(a, b);
+    let cost = mt.add(ab, c);
+    let x = mt.var(0, 1, 2);
+    let z = mt.var(2, 1, 2);
+    let prod = mt.mul(x, z);
+    let min = mt.min(cost, prod);
+    let max = mt.max(cost, prod);
+    let ge4 = mt.threshold(&mut ctx, cost, 4);
+    for r in 0..8u8 {
+        let value = |v: u8| r >> v & 1 == 1;
+        let (x0, x1, x2) = ((r & 1) as i64, (r >> 1 & 1) as i64, (r >> 2 & 1) as i64);
+        let c = 3 * x0 + 5 * x1 - 2 * x2;
+        let p = (1 + x0) * (1 + x2);
+        assert_eq!(cost.eval(value), c);
+        assert_eq!(prod.eval(value), p);
+        assert_eq!(min.eval(value), c.min(p));
+        assert_eq!(max.eval(value), c.max(p));
+        assert_eq!(ge4.eval(value), c >= 4);
+    }
+
+    // La somme sur toutes les affectations : 4·(3 + 5 - 2) = 24, et une
+    // variable absente double le résultat.
+    assert_eq!(mt.sum_abstract(cost, &[0, 1, 2]).leaf(), Some(24));
+    assert_eq!(mt.sum_abstract(cost, &[0, 1, 2, 3]).leaf(), Some(48));
+    let partial = mt.sum_abstract(cost, &[1]);
+    assert_eq!(partial.eval(|v| v == 0), 2 * 3 + 5);
+
+    // Hash-consing et réduction.
+    let zero = mt.constant(0);
+    assert!(mt.add(a, zero) == a);
+    let one = ctx.var(1);
+    assert!(mt.from_bdd(one, 0, 5) == b);
+    assert!(mt.var(3, 7, 7) == mt.constant(7));
+}
+
+#[test]
+fn test_mtbdd_float() {
+    let allo = Bump::new();
+    let mut mt: MtbddContext<u8, Float> = MtbddContext::new(&allo);
+    // 0.1 + 0.2 ne vaut pas exactement 0.3 en flottants, mais les deux
+    // valeurs partagent le même terminal.
+    let p = mt.constant(Float(0.1));
+    let q = mt.var(0, Float(0.2), Float(0.5));
+    let f = mt.add(p, q);
+    let r = mt.var(0, Float(0.3), Float(0.6));
+    assert!(f == r);
+    let total = mt.sum_abstract(f, &[0, 1]);
+    assert!(total == mt.constant(Float(1.8)));
+
+    // Les arrondis sont relatifs : les grandes valeurs ne sont pas confondues,
+    // et les petites ne sont pas confondues avec 0.
+    assert!(Float(1e12) != Float(1e12 + 1e3) && Float(1e12) == Float(1e12 + 1e-4));
+    assert!(Float(1e20) < Float(2e20) && Float(-1e20) > Float(-2e20));
+    assert!(Float(1e-12) != Float(0.) && Float(1e-12) == Float(1e-12 * (1. + 1e-15)));
+    assert!(Float(0.) == Float(-0.) && Float(-1.) < Float(0.) && Float(0.) < Float(1e-300));
+    assert!(Float(0.1 + 0.2) == Float(0.3));
+    assert!(Float(f64::INFINITY) > Float(f64::MAX / 2.));
+}
+
+#[test]
+#[should_panic(expected = "NaN value in an MTBDD")]
+fn test_float_nan() {
+    let allo = Bump::new();
+    let mut mt: MtbddContext<u8, Float> = MtbddContext::new(&allo);
+    mt.constant(Float(f64::NAN));
+}
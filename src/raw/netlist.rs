@@ -0,0 +1,158 @@
+// Export de BDDs sous forme de réseaux de multiplexeurs, en Verilog structurel
+// ou en BLIF (voir le module `blif` pour ce format).
+//
+// Chaque nœud interne du DAG partagé des BDDs devient un multiplexeur 2 vers 1
+// commandé par l'entrée correspondant à sa variable : sa sortie vaut celle du
+// fils `children[1]` si l'entrée vaut 1, et celle de `children[0]` sinon. Un
+// nœud partagé n'est écrit qu'une seule fois. Les signaux internes sont
+// nommés `_n<i>`, et en BLIF, les constantes sont les signaux `_c0` et `_c1` :
+// les noms des ports ne doivent donc pas commencer par `_`.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::{self, Write};
+use super::{Bdd, Context, Node, topological};
+
+// Le nom d'un nœud dans le réseau : un signal interne, une constante (dont
+// l'écriture dépend du format) ou une entrée.
+fn net<'arena, V>(b: Bdd<'arena, V>, ids: &HashMap<Bdd<'arena, V>, usize>,
+                  consts: [&str; 2]) -> String {
+    match b {
+        Bdd(Node::False) => consts[0].to_string(),
+        Bdd(Node::True) => consts[1].to_string(),
+        Bdd(Node::If { .. }) => format!("_n{}", ids[&b]),
+    }
+}
+
+type Network<'arena, 'a, V> = (Vec<Bdd<'arena, V>>, HashMap<Bdd<'arena, V>, usize>, Vec<&'a str>);
+
+impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
+    // La méthode privée `mux_network` renvoie les nœuds des BDDs de
+    // `outputs` dans l'ordre topologique, leurs numéros, et le nom du port
+    // d'entrée de chacun. Toutes les variables des BDDs doivent figurer dans
+    // `inputs`.
+    fn mux_network<'a>(&self, outputs: &[(&str, Bdd<'arena, V>)], inputs: &[(&'a str, V)])
+                       -> io::Result<Network<'arena, 'a, V>> {
+        let roots: Vec<Bdd<'arena, V>> = outputs.iter().map(|&(_, b)| b).collect();
+        let nodes = topological(&roots);
+        let ids = nodes.iter().enumerate().map(|(i, &b)| (b, i)).collect();
+        let mut sels = Vec::with_capacity(nodes.len());
+        for b in &nodes {
+            if let Bdd(Node::If { var, .. }) = b {
+                let (name, _) = inputs.iter().find(|&&(_, v)| v == *var).ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "variable missing from inputs")
+                })?;
+                sels.push(*name)
+            }
+        }
+        return Ok((nodes, ids, sels))
+    }
+
+    // La méthode `write_verilog` écrit un module Verilog `module` calculant
+    // les BDDs de `outputs`, chacun sur le port de sortie du nom associé.
+    // `inputs` donne le nom du port d'entrée de chaque variable.
+    pub fn write_verilog<W: Write>(&self, w: &mut W, module: &str,
+                                   inputs: &[(&str, V)], outputs: &[(&str, Bdd<'arena, V>)])
+                                   -> io::Result<()> {
+        let (nodes, ids, sels) = self.mux_network(outputs, inputs)?;
+        let inames: Vec<&str> = inputs.iter().map(|&(name, _)| name).collect();
+        let onames: Vec<&str> = outputs.iter().map(|&(name, _)| name).collect();
+        let consts = ["1'b0", "1'b1"];
+
+        writeln!(w, "module {}({});", module, [&inames[..], &onames[..]].concat().join(", "))?;
+        if !inames.is_empty() {
+            writeln!(w, "  input {};", inames.join(", "))?;
+        }
+        if !onames.is_empty() {
+            writeln!(w, "  output {};", onames.join(", "))?;
+        }
+        for (i, (&b, sel)) in nodes.iter().zip(sels).enumerate() {
+            if let Bdd(Node::If { children, .. }) = b {
+                writeln!(w, "  wire _n{} = {} ? {} : {};", i, sel,
+                         net(children[1], &ids, consts), net(children[0], &ids, consts))?;
+            }
+        }
+        for &(name, b) in outputs {
+            writeln!(w, "  assign {} = {};", name, net(b, &ids, consts))?;
+        }
+        writeln!(w, "endmodule")?;
+        return Ok(())
+    }
+
+    // La méthode `write_blif` écrit un modèle BLIF `model` calculant les BDDs
+    // de `outputs`, de la même manière que `write_verilog`.
+    pub fn write_blif<W: Write>(&self, w: &mut W, model: &str,
+                                inputs: &[(&str, V)], outputs: &[(&str, Bdd<'arena, V>)])
+                                -> io::Result<()> {
+        let (nodes, ids, sels) = self.mux_network(outputs, inputs)?;
+        let inames: Vec<&str> = inputs.iter().map(|&(name, _)| name).collect();
+        let onames: Vec<&str> = outputs.iter().map(|&(name, _)| name).collect();
+        let consts = ["_c0", "_c1"];
+
+        writeln!(w, ".model {}", model)?;
+        writeln!(w, ".inputs {}", inames.join(" "))?;
+        writeln!(w, ".outputs {}", onames.join(" "))?;
+        writeln!(w, ".names _c0")?;
+        writeln!(w, ".names _c1\n1")?;
+        for (i, (&b, sel)) in nodes.iter().zip(sels).enumerate() {
+            if let Bdd(Node::If { children, .. }) = b {
+                writeln!(w, ".names {} {} {} _n{}", sel,
+                         net(children[1], &ids, consts), net(children[0], &ids, consts), i)?;
+                writeln!(w, "11- 1\n0-1 1")?;
+            }
+        }
+        for &(name, b) in outputs {
+            writeln!(w, ".names {} {}\n1 1", net(b, &ids, consts), name)?;
+        }
+        writeln!(w, ".end")?;
+        return Ok(())
+    }
+}
+
+#[test]
+fn test_write_verilog() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let a = ctx.var(0u8);
+    let b = ctx.var(1);
+    let f = ctx.and(a, b);
+    let g = ctx.or(a, b);
+
+    let t = ctx.true_();
+    let mut buf = Vec::new();
+    ctx.write_verilog(&mut buf, "top", &[("a", 0), ("b", 1)],
+                      &[("f", f), ("g", g), ("one", t)]).unwrap();
+    assert_eq!(String::from_utf8(buf).unwrap(), "\
+module top(a, b, f, g, one);
+  input a, b;
+  output f, g, one;
+  wire _n0 = b ? 1'b1 : 1'b0;
+  wire _n1 = a ? _n0 : 1'b0;
+  wire _n2 = a ? 1'b1 : _n0;
+  assign f = _n1;
+  assign g = _n2;
+  assign one = 1'b1;
+endmodule
+");
+    assert!(ctx.write_verilog(&mut Vec::new(), "top", &[("a", 0)], &[("f", f)]).is_err());
+}
+
+#[test]
+fn test_write_blif() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let vars: Vec<Bdd<u8>> = (0..4).map(|i| ctx.var(i)).collect();
+    let x = ctx.xor(vars[0], vars[2]);
+    let y = ctx.and(vars[1], vars[3]);
+    let f = ctx.or(x, y);
+    let g = ctx.not(y);
+
+    // Le modèle écrit se relit en les mêmes BDDs.
+    let zero = ctx.false_();
+    let mut buf = Vec::new();
+    let inputs = [("a", 0), ("b", 1), ("c", 2), ("d", 3)];
+    ctx.write_blif(&mut buf, "test", &inputs, &[("f", f), ("g", g), ("zero", zero)]).unwrap();
+    let outs = ctx.read_blif(&mut &buf[..], |i| i as u8).unwrap();
+    assert_eq!(outs, vec![("f".to_string(), f), ("g".to_string(), g),
+                          ("zero".to_string(), zero)]);
+}
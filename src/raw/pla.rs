@@ -0,0 +1,245 @@
+// Import et export au format PLA de Berkeley, utilisé par l'outil Espresso.
+//
+// Un fichier PLA décrit une fonction booléenne à plusieurs sorties par une
+// liste de cubes. Il commence par des directives :
+//   - `.i n` et `.o m` donnent le nombre d'entrées et de sorties ;
+//   - `.ilb` et `.ob` (facultatives) donnent leurs noms ;
+//   - `.p k` (facultative) donne le nombre de cubes ;
+//   - `.type` (facultative) indique comment interpréter les sorties.
+// Chaque cube est ensuite décrit par une ligne formée d'une partie entrées de
+// `n` caractères (`0`, `1` ou `-`) et d'une partie sorties de `m` caractères.
+// Le fichier se termine par `.e`.
+//
+// À l'import, l'ensemble ON de chaque sortie est formé des cubes dont le
+// caractère correspondant vaut `1`. Dans un fichier de type `fd`, l'ensemble
+// DC (les termes indifférents) est formé des cubes dont le caractère vaut `-`
+// ou `2` ; dans un fichier de type `f` (le type par défaut), il est vide. Les
+// types `fr` et `fdr`, qui décrivent l'ensemble OFF, sont refusés plutôt
+// qu'interprétés partiellement.
+// À l'export, chaque BDD est écrit par une couverture irredondante (voir le
+// module `isop`), dans un fichier de type `f`.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::io::{self, Read, Write};
+use super::{Bdd, Context, invalid};
+use super::dddmp::parse;
+
+// Une sortie lue dans un fichier PLA : son nom, son ensemble ON et son
+// ensemble DC.
+pub type PlaOutput<'arena, V> = (String, Bdd<'arena, V>, Bdd<'arena, V>);
+
+impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
+    // La méthode `cube` construit directement le BDD d'une conjonction de
+    // littéraux `(variable, polarité)`.
+    pub fn cube(&mut self, lits: &[(V, bool)]) -> Bdd<'arena, V> {
+        return self.literals(lits, true)
+    }
+
+    // La méthode `read_pla` lit un fichier PLA, et renvoie pour chaque sortie
+    // son nom (vide si le fichier n'a pas de directive `.ob`), son ensemble
+    // ON et son ensemble DC. `var` associe à chaque numéro d'entrée (à partir
+    // de 0) la variable correspondante. Une couverture compatible avec les
+    // termes indifférents s'obtient par `isop_interval(on, on ∨ dc)`.
+    pub fn read_pla<R: Read, F>(&mut self, r: &mut R, var: F) -> io::Result<Vec<PlaOutput<'arena, V>>>
+      where F: Fn(usize) -> V {
+        let mut text = String::new();
+        r.read_to_string(&mut text)?;
+
+        let mut ninputs: Option<usize> = None;
+        let mut names: Vec<String> = Vec::new();
+        // Les ensembles ON et DC de chaque sortie.
+        let mut outputs: Option<Vec<[Bdd<'arena, V>; 2]>> = None;
+        let mut fd = false;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap().trim();
+            if line.is_empty() {
+                continue
+            }
+            let tokens: Vec<&str> = line.split_whitespace().collect();
+            match tokens[0] {
+                ".i" if tokens.len() == 2 => ninputs = Some(parse(tokens[1])?),
+                ".o" if tokens.len() == 2 => {
+                    let n: usize = parse(tokens[1])?;
+                    outputs = Some(vec![[self.false_(); 2]; n])
+                }
+                ".ob" => names = tokens[1..].iter().map(|s| s.to_string()).collect(),
+                ".type" if tokens.len() == 2 && (tokens[1] == "f" || tokens[1] == "fd") =>
+                    fd = tokens[1] == "fd",
+                ".type" => return Err(invalid("unsupported .type")),
+                ".e" | ".end" => break,
+                t if t.starts_with('.') => (),
+                _ => {
+                    let ninputs = ninputs.ok_or_else(|| invalid("missing .i directive"))?;
+                    let outputs = outputs.as_mut().ok_or_else(|| invalid("missing .o directive"))?;
+                    // Les parties entrées et sorties peuvent être séparées par
+                    // des espaces, ou non.
+                    let cube: String = tokens.concat();
+                    if cube.len() != ninputs + outputs.len() || !cube.is_ascii() {
+                        return Err(invalid("malformed cube"))
+                    }
+                    let (ins, outs) = cube.split_at(ninputs);
+                    let mut lits = Vec::new();
+                    for (i, c) in ins.chars().enumerate() {
+                        match c {
+                            '0' => lits.push((var(i), false)),
+                            '1' => lits.push((var(i), true)),
+                            '-' | '2' => (),
+                            _ => return Err(invalid("malformed cube"))
+                        }
+                    }
+                    let c = self.cube(&lits);
+                    for (o, ch) in outs.chars().enumerate() {
+                        match ch {
+                            '1' => outputs[o][0] = self.or(outputs[o][0], c),
+                            '-' | '2' if fd => outputs[o][1] = self.or(outputs[o][1], c),
+                            '0' | '-' | '~' | '2' => (),
+                            _ => return Err(invalid("malformed cube"))
+                        }
+                    }
+                }
+            }
+        }
+
+        let outputs = outputs.ok_or_else(|| invalid("missing .o directive"))?;
+        return Ok(outputs.into_iter().enumerate()
+                  .map(|(i, [on, dc])| (names.get(i).cloned().unwrap_or_default(), on, dc))
+                  .collect())
+    }
+
+    // La méthode `write_pla` écrit les BDDs de `outputs` au format PLA, sur
+    // les entrées `inputs`. Les entrées sont nommées `x<variable>`. Toutes les
+    // variables apparaissant dans les BDDs doivent figurer dans `inputs`. Les
+    // cubes communs à plusieurs sorties ne sont écrits qu'une seule fois.
+    pub fn write_pla<W: Write>(&mut self, w: &mut W, outputs: &[(&str, Bdd<'arena, V>)],
+                               inputs: &[V]) -> io::Result<()>
+      where V: Display {
+        let mut cubes: BTreeMap<Vec<u8>, Vec<u8>> = BTreeMap::new();
+        for (o, &(_, b)) in outputs.iter().enumerate() {
+            for cube in self.isop(b).0 {
+                let mut ins = vec![b'-'; inputs.len()];
+                for (v, pos) in cube {
+                    let i = inputs.iter().position(|&x| x == v).ok_or_else(|| {
+                        io::Error::new(io::ErrorKind::InvalidInput, "variable missing from inputs")
+                    })?;
+                    ins[i] = if pos { b'1' } else { b'0' };
+                }
+                cubes.entry(ins).or_insert_with(|| vec![b'0'; outputs.len()])[o] = b'1';
+            }
+        }
+
+        let inames: Vec<String> = inputs.iter().map(|v| format!("x{}", v)).collect();
+        let onames: Vec<&str> = outputs.iter().map(|&(name, _)| name).collect();
+        writeln!(w, ".i {}", inputs.len())?;
+        writeln!(w, ".o {}", outputs.len())?;
+        writeln!(w, ".ilb {}", inames.join(" "))?;
+        writeln!(w, ".ob {}", onames.join(" "))?;
+        writeln!(w, ".type f")?;
+        writeln!(w, ".p {}", cubes.len())?;
+        for (ins, outs) in &cubes {
+            writeln!(w, "{} {}", String::from_utf8_lossy(ins), String::from_utf8_lossy(outs))?;
+        }
+        writeln!(w, ".e")?;
+        return Ok(())
+    }
+}
+
+#[test]
+fn test_read_pla() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let text = "\
+# Un additionneur 1 bit
+.i 3
+.o 2
+.ilb a b cin
+.ob s cout
+.type fd
+.p 7
+100 10
+010 10
+001 10
+111 11
+11- 01
+1-1 01
+-11 01
+000 0-
+.e
+";
+    let outs = ctx.read_pla(&mut text.as_bytes(), |i| i as u8).unwrap();
+    let a = ctx.var(0);
+    let b = ctx.var(1);
+    let c = ctx.var(2);
+    let ab = ctx.xor(a, b);
+    let s = ctx.xor(ab, c);
+    let aob = ctx.or(a, b);
+    let ac = ctx.and(aob, c);
+    let aab = ctx.and(a, b);
+    let cout = ctx.or(aab, ac);
+    // Le terme indifférent de `cout` est l'affectation 000.
+    let fl = ctx.false_();
+    let nab = ctx.or(a, b);
+    let nabc = ctx.or(nab, c);
+    let none = ctx.not(nabc);
+    assert_eq!(outs, vec![("s".to_string(), s, fl), ("cout".to_string(), cout, none)]);
+
+    assert!(ctx.read_pla(&mut ".i 2\n.o 1\n1 1\n".as_bytes(), |i| i as u8).is_err());
+    assert!(ctx.read_pla(&mut "10 1\n".as_bytes(), |i| i as u8).is_err());
+    for t in ["fr", "fdr", "x"] {
+        let text = format!(".i 1\n.o 1\n.type {}\n1 1\n.e\n", t);
+        assert!(ctx.read_pla(&mut text.as_bytes(), |i| i as u8).is_err());
+    }
+}
+
+#[test]
+fn test_write_pla() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let a = ctx.var(0u8);
+    let b = ctx.var(1);
+    let c = ctx.var(2);
+    let ab = ctx.and(a, b);
+    let f = ctx.or(ab, c);
+
+    let mut buf = Vec::new();
+    ctx.write_pla(&mut buf, &[("f", f), ("ab", ab)], &[0, 1, 2]).unwrap();
+    let text = String::from_utf8(buf.clone()).unwrap();
+    assert_eq!(text, ".i 3\n.o 2\n.ilb x0 x1 x2\n.ob f ab\n.type f\n.p 2\n--1 10\n11- 11\n.e\n");
+    let fl = ctx.false_();
+    assert_eq!(ctx.read_pla(&mut &buf[..], |i| i as u8).unwrap(),
+               vec![("f".to_string(), f, fl), ("ab".to_string(), ab, fl)]);
+
+    assert!(ctx.write_pla(&mut Vec::new(), &[("f", f)], &[0, 1]).is_err());
+}
+
+#[test]
+fn test_pla_dont_care() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    // Un décodeur BCD vers « le chiffre est impair » : les codes 10 à 15
+    // sont indifférents.
+    let mut text = String::from(".i 4\n.o 1\n.type fd\n");
+    for n in 0..16 {
+        let out = if n >= 10 { '-' } else if n % 2 == 1 { '1' } else { '0' };
+        text.push_str(&format!("{:04b} {}\n", n, out));
+    }
+    text.push_str(".e\n");
+    let vars = [0u8, 1, 2, 3];
+    let outs = ctx.read_pla(&mut text.as_bytes(), |i| vars[i]).unwrap();
+    let (on, dc) = (outs[0].1, outs[0].2);
+    assert_eq!(on.nsat(&vars), 5);
+    assert_eq!(dc.nsat(&vars), 6);
+
+    // Les termes indifférents permettent une couverture par le seul
+    // littéral x3, qui est réécrite puis relue.
+    let upper = ctx.or(on, dc);
+    let (cover, f) = ctx.isop_interval(on, upper);
+    assert_eq!(cover.0, vec![vec![(3, true)]]);
+    let mut buf = Vec::new();
+    ctx.write_pla(&mut buf, &[("odd", f)], &vars).unwrap();
+    let back = ctx.read_pla(&mut &buf[..], |i| vars[i]).unwrap();
+    assert_eq!(back, vec![("odd".to_string(), f, ctx.false_())]);
+    let x3 = ctx.var(3);
+    assert_eq!(f, x3);
+}
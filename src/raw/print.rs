@@ -0,0 +1,141 @@
+// Affichage des BDDs.
+//
+// L'implémentation de `Debug` tient compte du partage : chaque nœud interne
+// accessible depuis le BDD est numéroté et affiché une seule fois, sous la
+// forme `n3 = if x2 then n1 else n0`, les fils étant toujours affichés avant
+// leurs pères. La taille de l'affichage est donc linéaire en le nombre de nœuds
+// du BDD.
+//
+// L'implémentation de `Display` affiche quant à elle une formule booléenne
+// lisible. Elle déplie le BDD comme un arbre, et n'est donc destinée qu'aux BDDs
+// de petite taille.
+
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Display, Formatter};
+use super::{Bdd, Node, topological};
+
+impl<'arena, V: Copy + Debug> Debug for Bdd<'arena, V> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let nodes = topological(&[*self]);
+        let ids: HashMap<Bdd<'arena, V>, usize> =
+            nodes.iter().enumerate().map(|(i, &b)| (b, i)).collect();
+        let name = |b: Bdd<'arena, V>| match b {
+            Bdd(Node::False) => "false".to_string(),
+            Bdd(Node::True) => "true".to_string(),
+            Bdd(Node::If { .. }) => format!("n{}", ids[&b]),
+        };
+
+        write!(f, "Bdd(")?;
+        for &b in &nodes {
+            if let Bdd(Node::If { var, children }) = b {
+                if f.alternate() {
+                    write!(f, "\n    ")?
+                }
+                write!(f, "{} = if x{:?} then {} else {};", name(b), var,
+                       name(children[1]), name(children[0]))?;
+                if !f.alternate() {
+                    write!(f, " ")?
+                }
+            }
+        }
+        if f.alternate() && !nodes.is_empty() {
+            write!(f, "\n    {}\n)", name(*self))
+        } else {
+            write!(f, "{})", name(*self))
+        }
+    }
+}
+
+// Niveaux de priorité des formules affichées par `Display`, utilisés pour
+// n'ajouter des parenthèses que lorsque c'est nécessaire.
+const PREC_OR: u8 = 0;
+const PREC_AND: u8 = 1;
+
+// La fonction `write_expr` affiche le BDD `b` dans un contexte de priorité
+// `prec` : si la formule produite est moins prioritaire, elle est entourée de
+// parenthèses.
+fn write_expr<V: Copy + Display>(b: Bdd<V>, prec: u8, f: &mut Formatter) -> fmt::Result {
+    let (var, children) = match b {
+        Bdd(Node::False) => return write!(f, "false"),
+        Bdd(Node::True) => return write!(f, "true"),
+        Bdd(Node::If { var, children }) => (var, children),
+    };
+    let [lo, hi] = *children;
+
+    // On simplifie les cas où l'un des fils est terminal, pour éviter des
+    // formules comme `x0 & true | !x0 & false`.
+    let expr_prec = match (lo.0, hi.0) {
+        (Node::False, Node::True) => return write!(f, "x{}", var),
+        (Node::True, Node::False) => return write!(f, "!x{}", var),
+        (_, Node::True) | (Node::True, _) => PREC_OR,
+        (_, Node::False) | (Node::False, _) => PREC_AND,
+        _ => PREC_OR,
+    };
+    if expr_prec < prec {
+        write!(f, "(")?
+    }
+    match (lo.0, hi.0) {
+        (_, Node::True) => { write!(f, "x{} | ", var)?; write_expr(lo, PREC_OR, f)? }
+        (Node::True, _) => { write!(f, "!x{} | ", var)?; write_expr(hi, PREC_OR, f)? }
+        (_, Node::False) => { write!(f, "!x{} & ", var)?; write_expr(lo, PREC_AND, f)? }
+        (Node::False, _) => { write!(f, "x{} & ", var)?; write_expr(hi, PREC_AND, f)? }
+        _ => {
+            write!(f, "x{} & ", var)?;
+            write_expr(hi, PREC_AND, f)?;
+            write!(f, " | !x{} & ", var)?;
+            write_expr(lo, PREC_AND, f)?
+        }
+    }
+    if expr_prec < prec {
+        write!(f, ")")?
+    }
+    return Ok(())
+}
+
+impl<'arena, V: Copy + Display> Display for Bdd<'arena, V> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write_expr(*self, PREC_OR, f)
+    }
+}
+
+#[test]
+fn test_debug() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = super::Context::new(&allo);
+    let x = ctx.var(0);
+    let y = ctx.var(1);
+    let xy = ctx.and(x, y);
+
+    assert_eq!(format!("{:?}", ctx.true_()), "Bdd(true)");
+    assert_eq!(format!("{:?}", ctx.false_()), "Bdd(false)");
+    assert_eq!(format!("{:?}", xy),
+               "Bdd(n0 = if x1 then true else false; n1 = if x0 then n0 else false; n1)");
+    assert_eq!(format!("{:#?}", xy),
+               "Bdd(\n    n0 = if x1 then true else false;\n    n1 = if x0 then n0 else false;\n    n1\n)");
+
+    // Le nœud partagé n'est affiché qu'une seule fois.
+    let z = ctx.var(2);
+    let yxz = ctx.xor(y, z);
+    let f = ctx.xor(x, yxz);
+    assert_eq!(format!("{:?}", f).matches(" = if ").count(), 5);
+}
+
+#[test]
+fn test_display() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = super::Context::new(&allo);
+    let x = ctx.var(0);
+    let y = ctx.var(1);
+    let z = ctx.var(2);
+    let nx = ctx.not(x);
+
+    assert_eq!(ctx.true_().to_string(), "true");
+    assert_eq!(ctx.false_().to_string(), "false");
+    assert_eq!(x.to_string(), "x0");
+    assert_eq!(nx.to_string(), "!x0");
+    assert_eq!(ctx.and(x, y).to_string(), "x0 & x1");
+    assert_eq!(ctx.or(x, y).to_string(), "x0 | x1");
+    let yoz = ctx.or(y, z);
+    assert_eq!(ctx.and(x, yoz).to_string(), "x0 & (x1 | x2)");
+    assert_eq!(ctx.xor(x, y).to_string(), "x0 & !x1 | !x0 & x1");
+}
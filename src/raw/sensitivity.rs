@@ -0,0 +1,151 @@
+// Développement de Shannon et sensibilité d'une fonction à ses variables.
+//
+// Les cofacteurs f₀ = f[x ← 0] et f₁ = f[x ← 1] d'un BDD s'obtiennent par
+// restriction : on recopie les nœuds au-dessus de x, et on remplace chaque
+// nœud de x par l'un de ses fils. La différence booléenne ∂f/∂x = f₀ ⊕ f₁ est
+// vraie pour les affectations des autres variables où changer x change f.
+// L'influence de x est la proportion de ces affectations, calculée par un
+// comptage de modèles mémoïsé : chaque nœud n'est visité qu'une fois, même
+// lorsqu'il est partagé.
+
+use std::collections::hash_map::HashMap;
+use std::hash::Hash;
+use super::{Bdd, Context, Node};
+
+impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
+    // La méthode privée `restrict` renvoie le cofacteur de `f` pour la
+    // valeur `value` de la variable `v`.
+    fn restrict(&mut self, f: Bdd<'arena, V>, v: V, value: bool,
+                memo: &mut HashMap<Bdd<'arena, V>, Bdd<'arena, V>>) -> Bdd<'arena, V> {
+        match f.0 {
+            Node::If { var, children } if *var <= v => {
+                if *var == v {
+                    return children[value as usize]
+                }
+                if let Some(&res) = memo.get(&f) {
+                    return res
+                }
+                let lo = self.restrict(children[0], v, value, memo);
+                let hi = self.restrict(children[1], v, value, memo);
+                let res = self.node(*var, [lo, hi]);
+                memo.insert(f, res);
+                return res
+            }
+            _ => return f
+        }
+    }
+
+    // La méthode `cofactors` renvoie les cofacteurs `(f[v ← 0], f[v ← 1])`.
+    pub fn cofactors(&mut self, f: Bdd<'arena, V>, v: V) -> (Bdd<'arena, V>, Bdd<'arena, V>) {
+        let f0 = self.restrict(f, v, false, &mut HashMap::new());
+        let f1 = self.restrict(f, v, true, &mut HashMap::new());
+        return (f0, f1)
+    }
+
+    // La méthode `boolean_difference` renvoie ∂f/∂v = f[v ← 0] ⊕ f[v ← 1].
+    pub fn boolean_difference(&mut self, f: Bdd<'arena, V>, v: V) -> Bdd<'arena, V> {
+        let (f0, f1) = self.cofactors(f, v);
+        return self.xor(f0, f1)
+    }
+
+    // La méthode `influence` renvoie la probabilité que changer la valeur de
+    // `v` change celle de `f`, pour une affectation uniforme des variables.
+    pub fn influence(&mut self, f: Bdd<'arena, V>, v: V) -> f64 {
+        let diff = self.boolean_difference(f, v);
+        return diff.density()
+    }
+
+    // La méthode `influences` renvoie l'influence de chaque variable du
+    // support de `f`, dans l'ordre croissant des variables.
+    pub fn influences(&mut self, f: Bdd<'arena, V>) -> Vec<(V, f64)> {
+        let vars = super::support(&super::topological(&[f]));
+        return vars.into_iter().map(|v| (v, self.influence(f, v))).collect()
+    }
+}
+
+impl<'arena, V: Hash + Copy + Ord> Bdd<'arena, V> {
+    // La méthode `is_sensitive_to` indique si `self` dépend de `v`. Dans un
+    // BDD réduit, c'est le cas si et seulement si un nœud teste `v` : il
+    // suffit donc de parcourir les nœuds dont la variable est plus petite que
+    // `v`, sans construire la différence booléenne.
+    pub fn is_sensitive_to(self, v: V) -> bool {
+        let mut stack = vec![self];
+        let mut seen = std::collections::HashSet::new();
+        while let Some(f) = stack.pop() {
+            if let Node::If { var, children } = f.0 {
+                if *var == v {
+                    return true
+                }
+                if *var < v && seen.insert(f) {
+                    stack.extend(children)
+                }
+            }
+        }
+        return false
+    }
+
+    // La méthode `density` renvoie la proportion des affectations qui
+    // satisfont `self`, ce qui ne dépend pas de l'ensemble des variables
+    // considérées.
+    pub fn density(self) -> f64 {
+        fn density_rec<'arena, V: Copy>(f: Bdd<'arena, V>, memo: &mut HashMap<Bdd<'arena, V>, f64>) -> f64 {
+            match f.0 {
+                Node::False => return 0.,
+                Node::True => return 1.,
+                Node::If { children, .. } => {
+                    if let Some(&p) = memo.get(&f) {
+                        return p
+                    }
+                    let p = (density_rec(children[0], memo) + density_rec(children[1], memo)) / 2.;
+                    memo.insert(f, p);
+                    return p
+                }
+            }
+        }
+        return density_rec(self, &mut HashMap::new())
+    }
+}
+
+#[test]
+fn test_sensitivity() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let x = ctx.var(0);
+    let y = ctx.var(1);
+    let z = ctx.var(2);
+    // f = x ∧ (y ∨ z)
+    let yz = ctx.or(y, z);
+    let f = ctx.and(x, yz);
+    let fl = ctx.false_();
+    assert!(ctx.cofactors(f, 0) == (fl, yz));
+    let nz = ctx.not(z);
+    let xnz = ctx.and(x, nz);
+    assert!(ctx.cofactors(f, 1) == (ctx.and(x, z), x));
+    assert_eq!(ctx.boolean_difference(f, 1), xnz);
+    assert!(ctx.cofactors(f, 5) == (f, f));
+    assert!(f.is_sensitive_to(2) && !f.is_sensitive_to(3));
+    assert_eq!(ctx.boolean_difference(f, 3), fl);
+
+    // Inf(x) = P(y ∨ z) = 3/4, Inf(y) = Inf(z) = P(x ∧ ¬z) = 1/4.
+    assert_eq!(ctx.influences(f), vec![(0, 0.75), (1, 0.25), (2, 0.25)]);
+    assert_eq!(ctx.influence(f, 7), 0.);
+    let p = ctx.xor(x, y);
+    let parity = ctx.xor(p, z);
+    assert_eq!(ctx.influences(parity), vec![(0, 1.), (1, 1.), (2, 1.)]);
+}
+
+#[test]
+fn test_sensitivity_shared() {
+    // La parité de 64 variables a un BDD de taille linéaire, mais un nombre
+    // exponentiel de chemins : les calculs doivent exploiter le partage.
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let mut f = ctx.false_();
+    for v in 0..64u8 {
+        let x = ctx.var(v);
+        f = ctx.xor(f, x)
+    }
+    assert!(f.is_sensitive_to(63));
+    assert_eq!(ctx.influence(f, 40), 1.);
+    assert_eq!(f.density(), 0.5);
+}
@@ -0,0 +1,194 @@
+// Tables de vérité et tableaux de Karnaugh, pour les fonctions ayant peu de
+// variables.
+//
+// Une table de vérité sur les variables `vars = [v0, ..., vn-1]` est un vecteur
+// de 2^n booléens, dont la case d'indice `r` donne la valeur de la fonction
+// pour l'affectation où `vi` vaut le bit de rang `n - 1 - i` de `r` : la
+// première variable est donc le bit de poids fort, comme dans une table de
+// vérité écrite à la main.
+
+use std::fmt::{Display, Write};
+use std::hash::Hash;
+use super::{Bdd, Context, Node};
+
+// La fonction `bits` écrit les `n` bits de poids faible de `r`, du poids fort
+// au poids faible.
+fn bits(r: usize, n: usize) -> String {
+    return (0..n).rev().map(|j| if r >> j & 1 == 1 { '1' } else { '0' }).collect()
+}
+
+impl<'arena, V: Hash + Copy + Ord> Bdd<'arena, V> {
+    // La méthode `eval` calcule la valeur du BDD pour l'affectation qui donne
+    // à chaque variable `v` la valeur `value(v)`.
+    pub fn eval<F: Fn(V) -> bool>(self, value: F) -> bool {
+        let mut b = self;
+        loop {
+            match b {
+                Bdd(Node::False) => return false,
+                Bdd(Node::True) => return true,
+                Bdd(Node::If { var, children }) => b = children[value(*var) as usize]
+            }
+        }
+    }
+
+    // La méthode `to_truth_table` renvoie la table de vérité du BDD sur les
+    // variables `vars`. Elle panique si une variable du BDD manque dans
+    // `vars`, ou si la table aurait plus de 2^(usize::BITS - 1) cases.
+    pub fn to_truth_table(self, vars: &[V]) -> Vec<bool> {
+        let n = vars.len();
+        assert!(n < usize::BITS as usize, "too many variables for a truth table");
+        let support = super::support(&super::topological(&[self]));
+        assert!(support.iter().all(|v| vars.contains(v)), "variable missing from vars");
+        return (0..1usize << n).map(|r| self.eval(|v| {
+            let i = vars.iter().position(|&x| x == v).unwrap();
+            r >> (n - 1 - i) & 1 == 1
+        })).collect()
+    }
+
+    // La méthode `render_truth_table` renvoie la table de vérité du BDD sur
+    // les variables `vars` sous forme de texte, une ligne par affectation.
+    // Elle panique dans les mêmes cas que `to_truth_table`.
+    pub fn render_truth_table(self, vars: &[V]) -> String
+      where V: Display {
+        let names: Vec<String> = vars.iter().map(|v| format!("x{}", v)).collect();
+        let table = self.to_truth_table(vars);
+        // L'écriture dans une `String` ne peut pas échouer : on ignore donc
+        // les résultats de `write!`.
+        let mut out = String::new();
+        let _ = writeln!(out, "{} | f", names.join(" "));
+        for (r, &b) in table.iter().enumerate() {
+            for (i, name) in names.iter().enumerate() {
+                let _ = write!(out, "{:>w$} ", r >> (vars.len() - 1 - i) & 1, w = name.len());
+            }
+            let _ = writeln!(out, "| {}", b as u8);
+        }
+        return out
+    }
+
+    // La méthode `render_karnaugh` renvoie le tableau de Karnaugh du BDD sur
+    // les variables `vars` sous forme de texte. Les lignes correspondent à la
+    // première moitié des variables, les colonnes à la seconde, et les unes
+    // comme les autres sont énumérées dans l'ordre du code de Gray, de sorte
+    // que deux cases voisines ne diffèrent que par une variable. Elle panique
+    // dans les mêmes cas que `to_truth_table`.
+    pub fn render_karnaugh(self, vars: &[V]) -> String
+      where V: Display {
+        let (rvars, cvars) = vars.split_at(vars.len() / 2);
+        let names = |vs: &[V]| vs.iter().map(|v| format!("x{}", v)).collect::<Vec<_>>().join(" ");
+        let label = format!("{} \\ {}", names(rvars), names(cvars));
+        let (nr, nc) = (rvars.len(), cvars.len());
+        let table = self.to_truth_table(vars);
+        let mut out = String::new();
+        let _ = write!(out, "{}", label);
+        for c in 0..1usize << nc {
+            let _ = write!(out, " {}", bits(c ^ c >> 1, nc));
+        }
+        let _ = writeln!(out);
+        for r in 0..1usize << nr {
+            let gr = r ^ r >> 1;
+            let _ = write!(out, "{:<w$}", bits(gr, nr), w = label.len());
+            for c in 0..1usize << nc {
+                let gc = c ^ c >> 1;
+                let _ = write!(out, " {:>w$}", table[gr << nc | gc] as u8, w = nc.max(1));
+            }
+            let _ = writeln!(out);
+        }
+        return out
+    }
+}
+
+impl<'arena, V: Hash + Copy + Ord> Context<'arena, V> {
+    // La méthode `from_truth_table` construit le BDD dont la table de vérité
+    // sur les variables `vars` est `bits`, qui doit être de longueur 2^n.
+    pub fn from_truth_table(&mut self, vars: &[V], bits: &[bool]) -> Bdd<'arena, V> {
+        assert_eq!(bits.len(), 1 << vars.len(), "truth table has the wrong length");
+        match vars.split_first() {
+            None => return if bits[0] { self.true_() } else { self.false_() },
+            Some((&v, rest)) => {
+                let (lo, hi) = bits.split_at(bits.len() / 2);
+                let lo = self.from_truth_table(rest, lo);
+                let hi = self.from_truth_table(rest, hi);
+                return self.branch(v, [lo, hi])
+            }
+        }
+    }
+}
+
+#[test]
+fn test_truth_table() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let x = ctx.var(0u8);
+    let y = ctx.var(1);
+    let z = ctx.var(2);
+    let xy = ctx.and(x, y);
+    let f = ctx.or(xy, z);
+
+    let table = f.to_truth_table(&[0, 1, 2]);
+    assert_eq!(table, [false, true, false, true, false, true, true, true]);
+    assert_eq!(ctx.from_truth_table(&[0, 1, 2], &table), f);
+    // L'ordre des variables de la table est indépendant de celui du BDD.
+    let table = f.to_truth_table(&[2, 1, 0]);
+    assert_eq!(table, [false, false, false, true, true, true, true, true]);
+    assert_eq!(ctx.from_truth_table(&[2, 1, 0], &table), f);
+    assert_eq!(ctx.true_().to_truth_table(&[]), [true]);
+    assert!(f.eval(|v| v != 2));
+    assert!(!f.eval(|v| v == 0));
+}
+
+#[test]
+fn test_truth_table_operators() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let x = ctx.var(0);
+    let y = ctx.var(1);
+    let vars = [0, 1];
+
+    let b = |t: &str| t.chars().map(|c| c == '1').collect::<Vec<bool>>();
+    assert_eq!(ctx.and(x, y).to_truth_table(&vars), b("0001"));
+    assert_eq!(ctx.or(x, y).to_truth_table(&vars), b("0111"));
+    assert_eq!(ctx.xor(x, y).to_truth_table(&vars), b("0110"));
+    assert_eq!(ctx.not(y).to_truth_table(&vars), b("1010"));
+    let nxy = ctx.from_truth_table(&vars, &b("0100"));
+    let nx = ctx.not(x);
+    assert_eq!(nxy, ctx.and(nx, y));
+}
+
+#[test]
+#[should_panic(expected = "variable missing from vars")]
+fn test_truth_table_missing_var() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let x = ctx.var(0u8);
+    let y = ctx.var(1);
+    let xy = ctx.and(x, y);
+    xy.to_truth_table(&[0]);
+}
+
+#[test]
+fn test_render() {
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let x = ctx.var(0u8);
+    let y = ctx.var(1);
+    let f = ctx.xor(x, y);
+    assert_eq!(f.render_truth_table(&[0, 1]), "\
+x0 x1 | f
+ 0  0 | 0
+ 0  1 | 1
+ 1  0 | 1
+ 1  1 | 0
+");
+
+    let vars: Vec<Bdd<u8>> = (0..4).map(|i| ctx.var(i)).collect();
+    let a = ctx.and(vars[0], vars[2]);
+    let b = ctx.and(vars[1], vars[3]);
+    let g = ctx.or(a, b);
+    assert_eq!(g.render_karnaugh(&[0, 1, 2, 3]), "\
+x0 x1 \\ x2 x3 00 01 11 10
+00             0  0  0  0
+01             0  1  1  0
+11             0  1  1  1
+10             0  0  1  1
+");
+}
@@ -0,0 +1,62 @@
+// Le cœur commun des diagrammes de décision de ce module autres que les BDDs
+// (MTBDDs, ZDDs et MDDs).
+//
+// Leurs nœuds sont alloués dans une arène et partagés par hash-consing : un
+// diagramme est un emprunt d'un nœud de l'arène, et deux diagrammes sont égaux
+// si et seulement si ils désignent le même nœud. La macro `handle!` donne aux
+// types de diagrammes ces comparaisons par adresse, et la table `Unique`
+// assure l'unicité des nœuds. Les BDDs ne l'utilisent pas : leurs terminaux
+// sont comparés par contenu (voir `Bdd`).
+
+use std::collections::hash_set::HashSet;
+use std::hash::Hash;
+use bumpalo::Bump;
+
+// La macro `handle!` implémente `Clone`, `Copy`, `PartialEq`, `Eq` et `Hash`
+// pour un type de diagramme `$t<'arena, ...>(&'arena N)`, par adresse. Un
+// diagramme est un emprunt : il est `Copy` quels que soient ses paramètres, ce
+// que `derive` ne permet pas d'exprimer.
+macro_rules! handle {
+    ($t:ident < $($p:ident),* >) => {
+        impl<'arena, $($p),*> Clone for $t<'arena, $($p),*> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+        impl<'arena, $($p),*> Copy for $t<'arena, $($p),*> { }
+
+        impl<'arena, $($p),*> PartialEq for $t<'arena, $($p),*> {
+            fn eq(&self, x: &Self) -> bool {
+                return std::ptr::eq(self.0, x.0)
+            }
+        }
+        impl<'arena, $($p),*> Eq for $t<'arena, $($p),*> { }
+        impl<'arena, $($p),*> std::hash::Hash for $t<'arena, $($p),*> {
+            fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+                std::ptr::hash(self.0, state)
+            }
+        }
+    }
+}
+
+// La table `Unique` alloue les nœuds de type `N` dans l'arène, en renvoyant
+// le nœud existant lorsqu'un nœud égal a déjà été alloué.
+pub struct Unique<'arena, N> {
+    alloc: &'arena Bump,
+    table: HashSet<&'arena N>,
+}
+
+impl<'arena, N: Hash + Eq> Unique<'arena, N> {
+    pub fn new(alloc: &'arena Bump) -> Self {
+        return Unique { alloc: alloc, table: HashSet::new() }
+    }
+
+    pub fn get(&mut self, n: N) -> &'arena N {
+        if let Some(&node) = self.table.get(&n) {
+            return node
+        }
+        let node = &*self.alloc.alloc(n);
+        self.table.insert(node);
+        return node
+    }
+}
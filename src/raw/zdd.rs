@@ -0,0 +1,415 @@
+// Diagrammes de décision à suppression de zéros (ZDDs).
+//
+// Un ZDD représente une famille d'ensembles de variables. Il a la même forme
+// qu'un BDD, mais une règle de réduction différente : on supprime les nœuds
+// dont le fils haut est la famille vide, au lieu des nœuds dont les deux fils
+// sont égaux. Une variable absente d'un chemin est donc absente des ensembles
+// correspondants, et non quelconque. Pour des familles d'ensembles petits
+// parmi beaucoup de variables, comme les solutions de problèmes
+// combinatoires creux, un ZDD est bien plus compact que le BDD de la fonction
+// caractéristique, et il ne dépend pas de l'univers des variables.
+//
+// Les ZDDs ont leur propre contexte, `ZddContext`, sur le modèle de
+// `Context` : les nœuds sont alloués dans une arène et partagés par
+// hash-consing, les terminaux compris, et les opérations binaires sont
+// mémoïsées dans le contexte. Les terminaux sont la famille vide `∅` et la
+// famille `{∅}`, qui ne contient que l'ensemble vide.
+
+use std::collections::hash_map::HashMap;
+use std::hash::Hash;
+use bumpalo::Bump;
+use super::{Bdd, Context, Node};
+use super::unique::Unique;
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+enum ZNode<'arena, V> {
+    Empty, Base,
+    If { var: V, children: [Zdd<'arena, V>; 2] }
+}
+
+pub struct Zdd<'arena, V>(&'arena ZNode<'arena, V>);
+
+handle!(Zdd<V>);
+
+// Les opérations binaires mémoïsées dans le contexte.
+#[derive(Copy, Clone, Eq, PartialEq, Hash)]
+enum ZOp { Union, Intersection, Difference, Join }
+
+type ZKey<'arena, V> = (ZOp, Zdd<'arena, V>, Zdd<'arena, V>);
+
+pub struct ZddContext<'arena, V> {
+    hashcons: Unique<'arena, ZNode<'arena, V>>,
+
+    // Pour les opérations commutatives, les clés sont ordonnées par adresse.
+    memo: HashMap<ZKey<'arena, V>, Zdd<'arena, V>>,
+}
+
+impl<'arena, V: Hash + Copy + Ord> ZddContext<'arena, V> {
+    pub fn new(alloc: &'arena Bump) -> Self {
+        return ZddContext { hashcons: Unique::new(alloc), memo: HashMap::new() }
+    }
+
+    fn hashcons(&mut self, n: ZNode<'arena, V>) -> Zdd<'arena, V> {
+        return Zdd(self.hashcons.get(n))
+    }
+
+    // La méthode privée `node` crée un nœud interne en appliquant la règle
+    // de suppression des zéros. Elle suppose que `var` est strictement plus
+    // petite que les variables de `children`.
+    fn node(&mut self, var: V, children: [Zdd<'arena, V>; 2]) -> Zdd<'arena, V> {
+        if let ZNode::Empty = children[1].0 {
+            return children[0]
+        }
+        return self.hashcons(ZNode::If { var: var, children: children })
+    }
+
+    // La méthode `empty` renvoie la famille vide.
+    pub fn empty(&mut self) -> Zdd<'arena, V> {
+        return self.hashcons(ZNode::Empty)
+    }
+
+    // La méthode `base` renvoie la famille `{∅}`.
+    pub fn base(&mut self) -> Zdd<'arena, V> {
+        return self.hashcons(ZNode::Base)
+    }
+
+    // La méthode `set` renvoie la famille réduite au seul ensemble `vars`.
+    pub fn set(&mut self, vars: &[V]) -> Zdd<'arena, V> {
+        let mut vars = vars.to_vec();
+        vars.sort();
+        vars.dedup();
+        let mut res = self.base();
+        for &v in vars.iter().rev() {
+            let empty = self.empty();
+            res = self.node(v, [empty, res])
+        }
+        return res
+    }
+
+    // La méthode privée `cofactors` renvoie les sous-familles de `f` des
+    // ensembles ne contenant pas `var` et des ensembles contenant `var`
+    // (privés de `var`), en supposant que `var` est au plus la variable de
+    // la racine de `f`.
+    fn cofactors(&mut self, f: Zdd<'arena, V>, var: V) -> (Zdd<'arena, V>, Zdd<'arena, V>) {
+        match f.0 {
+            ZNode::If { var: v, children } if *v == var => return (children[0], children[1]),
+            _ => {
+                let empty = self.empty();
+                return (f, empty)
+            }
+        }
+    }
+
+    // La méthode privée `apply` calcule une opération binaire, en
+    // décomposant les deux familles selon la plus petite de leurs variables
+    // de tête.
+    fn apply(&mut self, op: ZOp, a: Zdd<'arena, V>, b: Zdd<'arena, V>) -> Zdd<'arena, V> {
+        match (op, a.0, b.0) {
+            (ZOp::Union, ZNode::Empty, _) => return b,
+            (ZOp::Union, _, ZNode::Empty) => return a,
+            (ZOp::Union, _, _) if a == b => return a,
+            (ZOp::Intersection, ZNode::Empty, _) | (ZOp::Intersection, _, ZNode::Empty) => return self.empty(),
+            (ZOp::Intersection, _, _) if a == b => return a,
+            (ZOp::Difference, ZNode::Empty, _) => return a,
+            (ZOp::Difference, _, ZNode::Empty) => return a,
+            (ZOp::Difference, _, _) if a == b => return self.empty(),
+            (ZOp::Join, ZNode::Empty, _) | (ZOp::Join, _, ZNode::Empty) => return self.empty(),
+            (ZOp::Join, ZNode::Base, _) => return b,
+            (ZOp::Join, _, ZNode::Base) => return a,
+            _ => ()
+        }
+        let commutative = op != ZOp::Difference;
+        let key = if commutative && (b.0 as *const ZNode<V>) < (a.0 as *const ZNode<V>) { (op, b, a) } else { (op, a, b) };
+        if let Some(&res) = self.memo.get(&key) {
+            return res
+        }
+        let var = match (a.0, b.0) {
+            (ZNode::If { var: va, .. }, ZNode::If { var: vb, .. }) => *va.min(vb),
+            (ZNode::If { var, .. }, _) | (_, ZNode::If { var, .. }) => *var,
+            // Les cas où les deux familles sont des terminaux ont été traités.
+            _ => unreachable!()
+        };
+        let (a0, a1) = self.cofactors(a, var);
+        let (b0, b1) = self.cofactors(b, var);
+        let res = if op == ZOp::Join {
+            // (a0 ∪ v·a1) ⊔ (b0 ∪ v·b1) = a0⊔b0 ∪ v·(a1⊔b1 ∪ a1⊔b0 ∪ a0⊔b1)
+            let lo = self.apply(op, a0, b0);
+            let h11 = self.apply(op, a1, b1);
+            let h10 = self.apply(op, a1, b0);
+            let h01 = self.apply(op, a0, b1);
+            let h = self.apply(ZOp::Union, h11, h10);
+            let hi = self.apply(ZOp::Union, h, h01);
+            self.node(var, [lo, hi])
+        } else {
+            let lo = self.apply(op, a0, b0);
+            let hi = self.apply(op, a1, b1);
+            self.node(var, [lo, hi])
+        };
+        self.memo.insert(key, res);
+        return res
+    }
+
+    pub fn union(&mut self, a: Zdd<'arena, V>, b: Zdd<'arena, V>) -> Zdd<'arena, V> {
+        return self.apply(ZOp::Union, a, b)
+    }
+
+    pub fn intersection(&mut self, a: Zdd<'arena, V>, b: Zdd<'arena, V>) -> Zdd<'arena, V> {
+        return self.apply(ZOp::Intersection, a, b)
+    }
+
+    pub fn difference(&mut self, a: Zdd<'arena, V>, b: Zdd<'arena, V>) -> Zdd<'arena, V> {
+        return self.apply(ZOp::Difference, a, b)
+    }
+
+    // La méthode `join` renvoie la famille des unions `s ∪ t`, pour `s` dans
+    // `a` et `t` dans `b`.
+    pub fn join(&mut self, a: Zdd<'arena, V>, b: Zdd<'arena, V>) -> Zdd<'arena, V> {
+        return self.apply(ZOp::Join, a, b)
+    }
+
+    // La méthode privée `subset` parcourt `f` jusqu'à la variable `v`, et
+    // combine les deux fils du nœud de `v` (ou `f` lui-même, s'il ne contient
+    // pas `v`) par `at`. Elle factorise `change`, `onset` et `offset`.
+    fn subset<F>(&mut self, f: Zdd<'arena, V>, v: V, at: &F,
+                 memo: &mut HashMap<Zdd<'arena, V>, Zdd<'arena, V>>) -> Zdd<'arena, V>
+      where F: Fn(&mut Self, Zdd<'arena, V>, Zdd<'arena, V>) -> Zdd<'arena, V> {
+        match f.0 {
+            ZNode::If { var, children } if *var < v => {
+                if let Some(&res) = memo.get(&f) {
+                    return res
+                }
+                let lo = self.subset(children[0], v, at, memo);
+                let hi = self.subset(children[1], v, at, memo);
+                let res = self.node(*var, [lo, hi]);
+                memo.insert(f, res);
+                return res
+            }
+            ZNode::If { var, children } if *var == v => return at(self, children[0], children[1]),
+            _ => {
+                let empty = self.empty();
+                return at(self, f, empty)
+            }
+        }
+    }
+
+    // La méthode `change` ajoute `v` aux ensembles de `f` qui ne le
+    // contiennent pas, et le retire de ceux qui le contiennent.
+    pub fn change(&mut self, f: Zdd<'arena, V>, v: V) -> Zdd<'arena, V> {
+        return self.subset(f, v, &|ctx: &mut Self, lo, hi| ctx.node(v, [hi, lo]), &mut HashMap::new())
+    }
+
+    // La méthode `onset` renvoie les ensembles de `f` contenant `v`, privés
+    // de `v`.
+    pub fn onset(&mut self, f: Zdd<'arena, V>, v: V) -> Zdd<'arena, V> {
+        return self.subset(f, v, &|_: &mut Self, _, hi| hi, &mut HashMap::new())
+    }
+
+    // La méthode `offset` renvoie les ensembles de `f` ne contenant pas `v`.
+    pub fn offset(&mut self, f: Zdd<'arena, V>, v: V) -> Zdd<'arena, V> {
+        return self.subset(f, v, &|_: &mut Self, lo, _| lo, &mut HashMap::new())
+    }
+
+    // La méthode `from_bdd` renvoie la famille des ensembles `s ⊆ vars` dont
+    // la fonction caractéristique satisfait `b`. Le support de `b` doit être
+    // inclus dans `vars`.
+    pub fn from_bdd(&mut self, b: Bdd<'arena, V>, vars: &[V]) -> Zdd<'arena, V> {
+        let mut vars = vars.to_vec();
+        vars.sort();
+        vars.dedup();
+        return self.convert_rec(b, &vars, 0, &mut HashMap::new())
+    }
+
+    // La méthode privée `convert_rec` renvoie la famille des ensembles de
+    // variables parmi `vars[i..]` dont la fonction caractéristique satisfait
+    // `b`.
+    fn convert_rec(&mut self, b: Bdd<'arena, V>, vars: &[V], i: usize,
+                   memo: &mut HashMap<(Bdd<'arena, V>, usize), Zdd<'arena, V>>) -> Zdd<'arena, V> {
+        if i == vars.len() {
+            match b.0 {
+                Node::False => return self.empty(),
+                Node::True => return self.base(),
+                Node::If { .. } => panic!("BDD support not included in the variables")
+            }
+        }
+        if let Some(&res) = memo.get(&(b, i)) {
+            return res
+        }
+        let (lo, hi) = match b.0 {
+            Node::If { var, children } if *var == vars[i] => (children[0], children[1]),
+            Node::If { var, .. } if *var < vars[i] => panic!("BDD support not included in the variables"),
+            _ => (b, b)
+        };
+        let lo = self.convert_rec(lo, vars, i + 1, memo);
+        let hi = self.convert_rec(hi, vars, i + 1, memo);
+        let res = self.node(vars[i], [lo, hi]);
+        memo.insert((b, i), res);
+        return res
+    }
+
+    // La méthode `to_bdd` renvoie, dans le contexte `ctx`, la fonction
+    // caractéristique sur les variables `vars` de la famille `f`, dont les
+    // ensembles doivent être inclus dans `vars`.
+    pub fn to_bdd(&self, ctx: &mut Context<'arena, V>, f: Zdd<'arena, V>, vars: &[V]) -> Bdd<'arena, V> {
+        let mut vars = vars.to_vec();
+        vars.sort();
+        vars.dedup();
+        return to_bdd_rec(ctx, f, &vars, 0, &mut HashMap::new())
+    }
+}
+
+fn to_bdd_rec<'arena, V: Hash + Copy + Ord>(ctx: &mut Context<'arena, V>, f: Zdd<'arena, V>, vars: &[V], i: usize,
+                                            memo: &mut HashMap<(Zdd<'arena, V>, usize), Bdd<'arena, V>>)
+                                            -> Bdd<'arena, V> {
+    if i == vars.len() {
+        match f.0 {
+            ZNode::Empty => return ctx.false_(),
+            ZNode::Base => return ctx.true_(),
+            ZNode::If { .. } => panic!("ZDD sets not included in the variables")
+        }
+    }
+    if let Some(&res) = memo.get(&(f, i)) {
+        return res
+    }
+    let res = match f.0 {
+        ZNode::If { var, children } if *var == vars[i] => {
+            let lo = to_bdd_rec(ctx, children[0], vars, i + 1, memo);
+            let hi = to_bdd_rec(ctx, children[1], vars, i + 1, memo);
+            ctx.node(vars[i], [lo, hi])
+        }
+        ZNode::If { var, .. } if *var < vars[i] => panic!("ZDD sets not included in the variables"),
+        // `vars[i]` n'apparaît dans aucun ensemble : elle doit être fausse.
+        _ => {
+            let lo = to_bdd_rec(ctx, f, vars, i + 1, memo);
+            let hi = ctx.false_();
+            ctx.node(vars[i], [lo, hi])
+        }
+    };
+    memo.insert((f, i), res);
+    return res
+}
+
+impl<'arena, V: Hash + Copy + Ord> Zdd<'arena, V> {
+    // La méthode `count` renvoie le nombre d'ensembles de la famille, qui est
+    // le nombre de chemins vers le terminal `{∅}`.
+    pub fn count(self) -> u64 {
+        fn count_rec<'arena, V>(f: Zdd<'arena, V>, memo: &mut HashMap<Zdd<'arena, V>, u64>) -> u64 {
+            match f.0 {
+                ZNode::Empty => return 0,
+                ZNode::Base => return 1,
+                ZNode::If { children, .. } => {
+                    if let Some(&n) = memo.get(&f) {
+                        return n
+                    }
+                    let n = count_rec(children[0], memo) + count_rec(children[1], memo);
+                    memo.insert(f, n);
+                    return n
+                }
+            }
+        }
+        return count_rec(self, &mut HashMap::new())
+    }
+
+    // La méthode `iter` renvoie un itérateur sur les ensembles de la famille,
+    // chacun donné par la liste croissante de ses variables.
+    pub fn iter(self) -> ZddIter<'arena, V> {
+        return ZddIter { stack: vec![(self, vec![])] }
+    }
+}
+
+pub struct ZddIter<'arena, V> {
+    stack: Vec<(Zdd<'arena, V>, Vec<V>)>,
+}
+
+impl<'arena, V: Copy> Iterator for ZddIter<'arena, V> {
+    type Item = Vec<V>;
+    fn next(&mut self) -> Option<Vec<V>> {
+        while let Some((f, set)) = self.stack.pop() {
+            match f.0 {
+                ZNode::Empty => (),
+                ZNode::Base => return Some(set),
+                ZNode::If { var, children } => {
+                    let mut with = set.clone();
+                    with.push(*var);
+                    self.stack.push((children[1], with));
+                    self.stack.push((children[0], set))
+                }
+            }
+        }
+        return None
+    }
+}
+
+#[test]
+fn test_zdd() {
+    use std::collections::BTreeSet;
+    let allo = Bump::new();
+    let mut z: ZddContext<u8> = ZddContext::new(&allo);
+    type Family = BTreeSet<Vec<u8>>;
+    let family = |f: Zdd<u8>| -> Family { f.iter().collect() };
+
+    let sets_a: [&[u8]; 4] = [&[0, 2], &[1], &[], &[2, 3]];
+    let sets_b: [&[u8]; 3] = [&[1], &[3], &[0, 2]];
+    fn build<'arena>(z: &mut ZddContext<'arena, u8>, sets: &[&[u8]]) -> Zdd<'arena, u8> {
+        let mut f = z.empty();
+        for s in sets {
+            let s = z.set(s);
+            f = z.union(f, s)
+        }
+        return f
+    }
+    let a = build(&mut z, &sets_a);
+    let b = build(&mut z, &sets_b);
+    let fa: Family = sets_a.iter().map(|s| s.to_vec()).collect();
+    let fb: Family = sets_b.iter().map(|s| s.to_vec()).collect();
+    assert_eq!(family(a), fa);
+    assert_eq!(a.count(), 4);
+
+    assert_eq!(family(z.union(a, b)), fa.union(&fb).cloned().collect());
+    assert_eq!(family(z.intersection(a, b)), fa.intersection(&fb).cloned().collect());
+    assert_eq!(family(z.difference(a, b)), fa.difference(&fb).cloned().collect());
+    let mut join = Family::new();
+    for s in &fa {
+        for t in &fb {
+            let u: BTreeSet<u8> = s.iter().chain(t).copied().collect();
+            join.insert(u.into_iter().collect());
+        }
+    }
+    assert_eq!(family(z.join(a, b)), join);
+
+    let toggle = |s: &Vec<u8>, v: u8| -> Vec<u8> {
+        let mut s: BTreeSet<u8> = s.iter().copied().collect();
+        if !s.remove(&v) {
+            s.insert(v);
+        }
+        s.into_iter().collect()
+    };
+    for v in 0..5 {
+        assert_eq!(family(z.change(a, v)), fa.iter().map(|s| toggle(s, v)).collect());
+        assert_eq!(family(z.onset(a, v)),
+                   fa.iter().filter(|s| s.contains(&v)).map(|s| toggle(s, v)).collect());
+        assert_eq!(family(z.offset(a, v)), fa.iter().filter(|s| !s.contains(&v)).cloned().collect());
+    }
+}
+
+#[test]
+fn test_zdd_bdd() {
+    let allo = Bump::new();
+    let mut ctx = Context::new(&allo);
+    let mut z = ZddContext::new(&allo);
+    let vars: Vec<u16> = (0..20).collect();
+
+    // Les ensembles d'au plus 2 variables parmi 20 : le ZDD ne contient que
+    // les nœuds nécessaires, et la conversion est réversible.
+    let f = ctx.at_most(2, &vars);
+    let zf = z.from_bdd(f, &vars);
+    assert_eq!(zf.count(), 1 + 20 + 190);
+    assert_eq!(zf.count(), f.nsat(&vars));
+    assert!(z.to_bdd(&mut ctx, zf, &vars) == f);
+    assert!(zf.iter().all(|s| s.len() <= 2));
+
+    // Une variable hors du support du BDD double le nombre d'ensembles.
+    let x = ctx.var(3);
+    let zx = z.from_bdd(x, &[3, 5]);
+    assert_eq!(zx.iter().collect::<Vec<Vec<u16>>>(), vec![vec![3], vec![3, 5]]);
+    assert!(z.to_bdd(&mut ctx, zx, &[3, 5]) == x);
+}
@@ -0,0 +1,270 @@
+// Relations binaires sur les entiers non signés de taille fixe.
+//
+// Une `BddRelation` représente un ensemble de couples `(a, b)` par sa fonction
+// caractéristique, sur deux vecteurs de variables : les variables sources,
+// qui encodent `a`, et les variables cibles, qui encodent `b`. La composition
+// utilise un troisième vecteur de variables, intermédiaire. Les trois vecteurs
+// sont entrelacés (voir `BitVec::interleaved`), ce qui garde petits les BDDs
+// des relations usuelles, comme l'égalité ou la comparaison.
+//
+// Les ensembles de sources ou de cibles sont des `BddSet` sur les variables
+// sources : l'image d'un ensemble est ainsi un ensemble du même domaine, que
+// l'on peut de nouveau passer à `image`.
+//
+// Comme pour `BddSet`, les éléments sont des `u64` : la largeur est d'au plus
+// 64 bits, et insérer un couple dont un élément ne s'écrit pas sur cette
+// largeur est une erreur.
+
+use std::collections::HashMap;
+use std::hash::Hash;
+use super::{Bdd, BddSet, Context};
+
+#[derive(Clone)]
+pub struct BddRelation<'arena, V> {
+    ctx: Context<'arena, V>,
+    src: Vec<V>,
+    dst: Vec<V>,
+    tmp: Vec<V>,
+    f: Bdd<'arena, V>,
+}
+
+impl<'arena, V: Hash + Copy + Ord> BddRelation<'arena, V> {
+    // La fonction `empty` renvoie la relation vide entre entiers de `width`
+    // bits. Le bit `j` de la source est la variable `var(3 * j)`, celui de la
+    // cible `var(3 * j + 1)`, et `var(3 * j + 2)` est réservée à la
+    // composition.
+    pub fn empty<F: Fn(usize) -> V>(ctx: Context<'arena, V>, width: usize, var: F) -> Self {
+        assert!(width <= 64, "relation elements wider than 64 bits");
+        let vars = |k: usize| (0..width).map(|j| var(3 * j + k)).collect();
+        return BddRelation { ctx: ctx, src: vars(0), dst: vars(1), tmp: vars(2), f: ctx.false_() }
+    }
+
+    pub fn width(&self) -> usize {
+        return self.src.len()
+    }
+
+    pub fn bdd(&self) -> Bdd<'arena, V> {
+        return self.f
+    }
+
+    // La méthode `with_bdd` renvoie la relation de fonction caractéristique
+    // `f`, sur les mêmes variables que `self`. `f` ne doit dépendre que des
+    // variables sources et cibles.
+    pub fn with_bdd(&self, f: Bdd<'arena, V>) -> Self {
+        return BddRelation { f: f, ..self.clone() }
+    }
+
+    // La méthode `identity` renvoie la relation d'égalité, sur les mêmes
+    // variables que `self`.
+    pub fn identity(&self) -> Self {
+        let f = self.src.iter().zip(&self.dst)
+            .fold(self.ctx.true_(), |acc, (&a, &b)| acc & !(self.ctx.var(a) ^ self.ctx.var(b)));
+        return self.with_bdd(f)
+    }
+
+    // La méthode `empty_set` renvoie l'ensemble vide du domaine de la
+    // relation, à passer à `image` et `preimage`.
+    pub fn empty_set(&self) -> BddSet<'arena, V> {
+        return BddSet::empty(self.ctx, &self.src)
+    }
+
+    // La méthode privée `encode` renvoie le BDD vrai lorsque les variables
+    // `vars` encodent `x`.
+    fn encode(&self, vars: &[V], x: u64) -> Bdd<'arena, V> {
+        return vars.iter().enumerate().fold(self.ctx.true_(), |acc, (i, &v)| {
+            let b = self.ctx.var(v);
+            acc & if x >> i & 1 == 1 { b } else { !b }
+        })
+    }
+
+    // La méthode privée `fits` indique si `x` s'écrit sur la largeur de la
+    // relation.
+    fn fits(&self, x: u64) -> bool {
+        return self.width() == 64 || x >> self.width() == 0
+    }
+
+    // La méthode `insert` ajoute le couple `(a, b)` à la relation. Elle
+    // panique si `a` ou `b` ne s'écrit pas sur la largeur de la relation.
+    pub fn insert(&mut self, a: u64, b: u64) {
+        assert!(self.fits(a) && self.fits(b), "element too wide for the relation");
+        self.f |= self.encode(&self.src, a) & self.encode(&self.dst, b)
+    }
+
+    pub fn contains(&self, a: u64, b: u64) -> bool {
+        if !self.fits(a) || !self.fits(b) {
+            return false
+        }
+        return self.f.eval(|v| {
+            if let Some(i) = self.src.iter().position(|&w| w == v) {
+                return a >> i & 1 == 1
+            }
+            let i = self.dst.iter().position(|&w| w == v).unwrap();
+            return b >> i & 1 == 1
+        })
+    }
+
+    // La méthode `len` renvoie le nombre de couples de la relation.
+    pub fn len(&self) -> u64 {
+        let mut vars = [&self.src[..], &self.dst[..]].concat();
+        vars.sort();
+        return self.f.nsat(&vars)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.f == self.ctx.false_()
+    }
+
+    // La méthode privée `rename` renvoie `f` où chaque variable de `from` est
+    // remplacée par la variable de même position dans `to`.
+    fn rename(&self, f: Bdd<'arena, V>, from: &[V], to: &[V]) -> Bdd<'arena, V> {
+        let map: HashMap<V, V> = from.iter().copied().zip(to.iter().copied()).collect();
+        return f.rename(|v| *map.get(&v).unwrap_or(&v))
+    }
+
+    // Les méthodes `union`, `intersection` et `compose` supposent que les
+    // deux relations sont sur les mêmes variables.
+    pub fn union(&self, other: &Self) -> Self {
+        assert!(self.src == other.src && self.dst == other.dst, "relations over different variables");
+        return self.with_bdd(self.f | other.f)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        assert!(self.src == other.src && self.dst == other.dst, "relations over different variables");
+        return self.with_bdd(self.f & other.f)
+    }
+
+    // La méthode `compose` renvoie la relation `self` suivie de `other` :
+    // l'ensemble des `(a, c)` tels qu'il existe `b` avec `(a, b)` dans `self`
+    // et `(b, c)` dans `other`.
+    pub fn compose(&self, other: &Self) -> Self {
+        assert!(self.src == other.src && self.dst == other.dst, "relations over different variables");
+        let left = self.rename(self.f, &self.dst, &self.tmp);
+        let right = self.rename(other.f, &self.src, &self.tmp);
+        return self.with_bdd((left & right).exists(&self.tmp))
+    }
+
+    // La méthode `inverse` renvoie la relation des couples `(b, a)` tels que
+    // `(a, b)` est dans `self`.
+    pub fn inverse(&self) -> Self {
+        let from = [&self.src[..], &self.dst[..]].concat();
+        let to = [&self.dst[..], &self.src[..]].concat();
+        return self.with_bdd(self.rename(self.f, &from, &to))
+    }
+
+    // La méthode `image` renvoie l'ensemble des `b` tels que `(a, b)` est dans
+    // la relation pour un `a` de `set`.
+    pub fn image(&self, set: &BddSet<'arena, V>) -> BddSet<'arena, V> {
+        assert!(set.vars() == &self.src[..], "set over different variables");
+        let f = (set.bdd() & self.f).exists(&self.src);
+        return BddSet::from_bdd(self.ctx, &self.src, self.rename(f, &self.dst, &self.src))
+    }
+
+    // La méthode `preimage` renvoie l'ensemble des `a` tels que `(a, b)` est
+    // dans la relation pour un `b` de `set`.
+    pub fn preimage(&self, set: &BddSet<'arena, V>) -> BddSet<'arena, V> {
+        assert!(set.vars() == &self.src[..], "set over different variables");
+        let target = self.rename(set.bdd(), &self.src, &self.dst);
+        return BddSet::from_bdd(self.ctx, &self.src, (target & self.f).exists(&self.dst))
+    }
+
+    // La méthode `transitive_closure` calcule la fermeture transitive de la
+    // relation par élévations au carré successives : après k itérations de
+    // R ← R ∪ R∘R, R contient les chemins de longueur au plus 2^k, et le
+    // point fixe est atteint en un nombre d'itérations logarithmique en la
+    // longueur du plus long chemin.
+    pub fn transitive_closure(&self) -> Self {
+        let mut r = self.clone();
+        loop {
+            let next = r.union(&r.compose(&r));
+            if next.f == r.f {
+                return r
+            }
+            r = next
+        }
+    }
+}
+
+#[test]
+fn test_relation() {
+    super::use_bdd(|ctx| {
+        // Un graphe sur 8 sommets : une chaîne 0 → 1 → ... → 5, et un cycle
+        // 6 → 7 → 6.
+        let mut r = BddRelation::empty(ctx, 3, |i| i as u8);
+        let edges = [(0, 1), (1, 2), (2, 3), (3, 4), (4, 5), (6, 7), (7, 6)];
+        for &(a, b) in &edges {
+            r.insert(a, b)
+        }
+        assert_eq!(r.len(), 7);
+        assert!(r.contains(6, 7) && !r.contains(1, 0));
+        assert!(r.inverse().contains(1, 0) && !r.inverse().contains(0, 1));
+
+        let r2 = r.compose(&r);
+        let pairs = |r: &BddRelation<u8>| {
+            let mut res = vec![];
+            for a in 0..8 {
+                for b in 0..8 {
+                    if r.contains(a, b) {
+                        res.push((a, b))
+                    }
+                }
+            }
+            res
+        };
+        assert_eq!(pairs(&r2), vec![(0, 2), (1, 3), (2, 4), (3, 5), (6, 6), (7, 7)]);
+        assert_eq!(r.compose(&r.identity()).bdd(), r.bdd());
+
+        let tc = r.transitive_closure();
+        let mut expected = vec![];
+        for a in 0..6 {
+            for b in a + 1..6 {
+                expected.push((a, b))
+            }
+        }
+        expected.extend([(6, 6), (6, 7), (7, 6), (7, 7)]);
+        expected.sort();
+        assert_eq!(pairs(&tc), expected);
+        assert_eq!(tc.union(&r).bdd(), tc.bdd());
+        assert_eq!(tc.intersection(&r).bdd(), r.bdd());
+
+        let mut s = r.empty_set();
+        s.insert(2);
+        s.insert(6);
+        assert_eq!(tc.image(&s).iter().collect::<Vec<u64>>(), vec![3, 4, 5, 6, 7]);
+        assert_eq!(tc.preimage(&s).iter().collect::<Vec<u64>>(), vec![0, 1, 6, 7]);
+        assert_eq!(r.image(&s).iter().collect::<Vec<u64>>(), vec![3, 7]);
+    });
+}
+
+#[test]
+fn test_relation_width() {
+    super::use_bdd(|ctx| {
+        let mut r = BddRelation::empty(ctx, 3, |i| i as u8);
+        r.insert(0, 1);
+        r.insert(7, 7);
+        assert!(r.contains(0, 1) && r.contains(7, 7));
+        assert!(!r.contains(8, 1) && !r.contains(0, 9) && !r.contains(u64::MAX, 7));
+
+        // Les éléments de 64 bits occupent toute la largeur d'un `u64`.
+        let mut r = BddRelation::empty(ctx, 64, |i| i as u8);
+        r.insert(u64::MAX, 0);
+        assert!(r.contains(u64::MAX, 0) && !r.contains(u64::MAX, 1));
+        assert_eq!(r.len(), 1);
+    });
+}
+
+#[test]
+#[should_panic(expected = "element too wide for the relation")]
+fn test_relation_insert_too_wide() {
+    super::use_bdd(|ctx| {
+        let mut r = BddRelation::empty(ctx, 3, |i| i as u8);
+        r.insert(8, 1);
+    });
+}
+
+#[test]
+#[should_panic(expected = "relation elements wider than 64 bits")]
+fn test_relation_too_wide() {
+    super::use_bdd(|ctx| {
+        BddRelation::empty(ctx, 65, |i| i as u16);
+    });
+}
@@ -0,0 +1,67 @@
+// Compilation du code produit par `to_rust`. Ce test lance `rustc` et exécute
+// le programme obtenu : il dépend donc de l'environnement, et n'est exécuté
+// que sur demande (`cargo test -- --ignored`).
+
+use std::fmt::Write;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+use bdd::raw::Context;
+
+#[test]
+#[ignore]
+fn test_codegen_compile() {
+    // Le code produit pour un BDD de plusieurs milliers de nœuds est compilé
+    // par `rustc`, puis exécuté sur des entrées pseudo-aléatoires ; ses
+    // résultats doivent coïncider avec l'évaluation du BDD.
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    if Command::new(&rustc).arg("--version").output().is_err() {
+        eprintln!("rustc unavailable, test skipped");
+        return
+    }
+
+    let allo = bumpalo::Bump::new();
+    let mut ctx = Context::new(&allo);
+    let n = 24u16;
+    let mut f = ctx.false_();
+    for i in 0..n / 2 {
+        let x = ctx.var(i);
+        let y = ctx.var(i + n / 2);
+        let xy = ctx.and(x, y);
+        f = ctx.xor(f, xy)
+    }
+    let code = f.to_rust("f", |v| v as usize);
+    // Une branche du `match` par nœud.
+    assert!(code.matches(" => if x[").count() > 1000);
+
+    let mut seed = 0x2545f4914f6cdd1du64;
+    let mut inputs = Vec::new();
+    for _ in 0..200 {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        inputs.push((0..n).map(|i| (seed >> i) & 1 == 1).collect::<Vec<bool>>())
+    }
+    let mut main = String::from("fn main() {\n    let inputs: &[&[bool]] = &[\n");
+    for x in &inputs {
+        let _ = writeln!(main, "        &{:?},", x);
+    }
+    main.push_str("    ];\n    for x in inputs {\n        print!(\"{}\", f(x) as u8);\n    }\n}\n");
+
+    // Un répertoire propre à cette exécution, pour que des exécutions
+    // simultanées ne se gênent pas.
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+    let dir = std::env::temp_dir().join(format!("bdd-codegen-{}-{}", std::process::id(), nanos));
+    std::fs::create_dir(&dir).unwrap();
+    let src = dir.join("f.rs");
+    std::fs::write(&src, code + &main).unwrap();
+    let status = Command::new(&rustc).arg("--edition=2021").arg("-o").arg(dir.join("f"))
+        .arg(&src).status().unwrap();
+    let output = Command::new(dir.join("f")).output();
+    std::fs::remove_dir_all(&dir).unwrap();
+    assert!(status.success());
+
+    let expected: String = inputs.iter()
+        .map(|x| if f.eval(|v| x[v as usize]) { '1' } else { '0' })
+        .collect();
+    assert_eq!(String::from_utf8(output.unwrap().stdout).unwrap(), expected);
+}